@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE user_settings DROP COLUMN registration_expiry_days;
+DROP TABLE user_registrations;
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+CREATE TABLE user_registrations (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NULL REFERENCES users (id) ON DELETE SET NULL,
+    name VARCHAR(255) NOT NULL,
+    ip VARCHAR(45) NULL,
+    user_agent VARCHAR(512) NULL,
+    status INTEGER NOT NULL DEFAULT 0,
+    reason TEXT NULL,
+    reviewed_by INTEGER NULL REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    reviewed_at TIMESTAMP NULL
+);
+
+CREATE INDEX user_registrations_status_idx ON user_registrations (status, created_at);
+
+-- Existing accounts enter the queue now, so they don't expire right after the deploy
+INSERT INTO user_registrations (user_id, name)
+SELECT id, name FROM users WHERE confirmed = FALSE AND deleted_at IS NULL;
+
+ALTER TABLE user_settings ADD COLUMN registration_expiry_days INTEGER NULL;
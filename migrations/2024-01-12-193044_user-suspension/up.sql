@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE users
+    ADD COLUMN suspended_until TIMESTAMP NULL,
+    ADD COLUMN suspension_reason TEXT NULL;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE user_registrations DROP COLUMN invitation_code;
+DROP TABLE invitation_codes;
+ALTER TABLE user_settings DROP COLUMN registration_mode;
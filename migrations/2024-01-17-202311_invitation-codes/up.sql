@@ -0,0 +1,16 @@
+-- Your SQL goes here
+ALTER TABLE user_settings ADD COLUMN registration_mode INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE invitation_codes (
+    code VARCHAR(64) PRIMARY KEY,
+    max_uses INTEGER NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    role INTEGER NULL,
+    auto_confirm BOOLEAN NOT NULL DEFAULT FALSE,
+    expires_at TIMESTAMP NULL,
+    created_by INTEGER NULL REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp
+);
+
+ALTER TABLE user_registrations
+    ADD COLUMN invitation_code VARCHAR(64) NULL REFERENCES invitation_codes (code) ON DELETE SET NULL;
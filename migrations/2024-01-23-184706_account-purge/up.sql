@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE user_settings ADD COLUMN deleted_user_retention_days INTEGER NULL;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+CREATE TABLE user_logins (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NULL REFERENCES users (id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    ip VARCHAR(45) NULL,
+    user_agent VARCHAR(512) NULL,
+    method INTEGER NOT NULL DEFAULT 0,
+    success BOOLEAN NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp
+);
+
+CREATE INDEX user_logins_user_id_idx ON user_logins (user_id, created_at);
+
+ALTER TABLE users ADD COLUMN last_login_at TIMESTAMP NULL;
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE user_settings DROP COLUMN allowed_scripts;
+
+DROP INDEX users_name_skeleton_idx;
+
+UPDATE users SET normalized_name = lower(name);
+
+ALTER TABLE users
+DROP COLUMN name_conflict,
+DROP COLUMN name_skeleton;
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+ALTER TABLE users
+ADD COLUMN name_skeleton VARCHAR(255) NULL,
+ADD COLUMN name_conflict BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Names are normalized with NFKC, the ones that would collide keep their old form and are marked for an admin to resolve
+WITH normalized AS (
+    SELECT id,
+        lower(normalize(btrim(name), NFKC)) AS value,
+        count(*) OVER (PARTITION BY lower(normalize(btrim(name), NFKC))) AS matches
+    FROM users
+)
+UPDATE users
+SET normalized_name = CASE WHEN normalized.matches = 1 THEN normalized.value ELSE users.normalized_name END,
+    name_conflict = normalized.matches > 1
+FROM normalized
+WHERE users.id = normalized.id;
+
+-- Skeletons need the Unicode confusables data, they are filled in on startup.
+-- Marked users keep their skeleton so look-alike names are still caught, but it doesn't have to be unique.
+CREATE UNIQUE INDEX users_name_skeleton_idx ON users (name_skeleton) WHERE NOT name_conflict;
+
+ALTER TABLE user_settings ADD COLUMN allowed_scripts VARCHAR(255) NULL;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TABLE reserved_names (
+    id SERIAL PRIMARY KEY,
+    pattern VARCHAR(255) NOT NULL,
+    kind INTEGER NOT NULL DEFAULT 0,
+    reason TEXT NULL,
+    created_by INTEGER NULL REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    UNIQUE (pattern, kind)
+);
+
+INSERT INTO reserved_names (pattern, reason)
+VALUES
+    ('admin', 'System account'),
+    ('administrator', 'System account'),
+    ('root', 'System account'),
+    ('support', 'System account'),
+    ('system', 'System account');
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE user_preferences (
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    key VARCHAR(64) NOT NULL,
+    value TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    PRIMARY KEY (user_id, key)
+);
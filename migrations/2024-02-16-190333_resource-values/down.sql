@@ -0,0 +1,16 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE resources
+    ADD COLUMN en TEXT NULL,
+    ADD COLUMN pl TEXT NULL;
+
+UPDATE resources SET
+    en = (SELECT value FROM resource_values v WHERE v.key = resources.key AND v.lang = 'en'),
+    pl = (SELECT value FROM resource_values v WHERE v.key = resources.key AND v.lang = 'pl');
+
+UPDATE resources SET en = '' WHERE en IS NULL;
+
+ALTER TABLE resources ALTER COLUMN en SET NOT NULL;
+
+DROP TABLE resource_values;
+
+DROP TABLE languages;
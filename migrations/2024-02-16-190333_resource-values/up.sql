@@ -0,0 +1,27 @@
+-- Your SQL goes here
+CREATE TABLE languages (
+    code VARCHAR(16) PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp
+);
+
+INSERT INTO languages (code, name) VALUES
+('en', 'English'),
+('pl', 'Polski');
+
+CREATE TABLE resource_values (
+    key VARCHAR(64) NOT NULL REFERENCES resources (key) ON DELETE CASCADE,
+    lang VARCHAR(16) NOT NULL REFERENCES languages (code),
+    value TEXT NOT NULL,
+    PRIMARY KEY (key, lang)
+);
+
+INSERT INTO resource_values (key, lang, value)
+SELECT key, 'en', en FROM resources;
+
+INSERT INTO resource_values (key, lang, value)
+SELECT key, 'pl', pl FROM resources WHERE pl IS NOT NULL;
+
+ALTER TABLE resources
+    DROP COLUMN en,
+    DROP COLUMN pl;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE languages ADD COLUMN fallback VARCHAR(16) NULL REFERENCES languages (code) ON DELETE SET NULL;
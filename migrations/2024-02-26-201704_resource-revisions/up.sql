@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE resource_revisions (
+    id SERIAL PRIMARY KEY,
+    key VARCHAR(64) NOT NULL REFERENCES resources (key) ON DELETE CASCADE,
+    lang VARCHAR(16) NOT NULL,
+    value TEXT NULL,
+    author INTEGER NULL REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp
+);
+
+CREATE INDEX resource_revisions_key_idx ON resource_revisions (key, lang, created_at);
+
+INSERT INTO resource_revisions (key, lang, value)
+SELECT key, lang, value FROM resource_values;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE resource_drafts (
+    key VARCHAR(64) NOT NULL REFERENCES resources (key) ON DELETE CASCADE,
+    lang VARCHAR(16) NOT NULL REFERENCES languages (code),
+    value TEXT NOT NULL,
+    author INTEGER NULL REFERENCES users (id) ON DELETE SET NULL,
+    publish_at TIMESTAMP NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    PRIMARY KEY (key, lang)
+);
+
+CREATE INDEX resource_drafts_publish_at_idx ON resource_drafts (publish_at);
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER resource_search_reindex ON languages;
+DROP FUNCTION resource_search_reindex;
+DROP TRIGGER resource_search_update ON resource_values;
+DROP FUNCTION resource_search_update;
+DROP TABLE resource_search;
+
+ALTER TABLE languages
+DROP COLUMN search_config;
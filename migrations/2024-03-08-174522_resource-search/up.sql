@@ -0,0 +1,49 @@
+-- Your SQL goes here
+ALTER TABLE languages
+ADD COLUMN search_config VARCHAR(64) NOT NULL DEFAULT 'simple';
+
+UPDATE languages SET search_config = 'english' WHERE code = 'en';
+
+CREATE TABLE resource_search (
+    key VARCHAR(64) NOT NULL,
+    lang VARCHAR(16) NOT NULL,
+    document TSVECTOR NOT NULL,
+    PRIMARY KEY (key, lang),
+    FOREIGN KEY (key, lang) REFERENCES resource_values (key, lang) ON DELETE CASCADE
+);
+
+CREATE INDEX resource_search_document_idx ON resource_search USING GIN (document);
+
+CREATE FUNCTION resource_search_update() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO resource_search (key, lang, document)
+    SELECT NEW.key, NEW.lang, to_tsvector(l.search_config::regconfig, NEW.value)
+    FROM languages l
+    WHERE l.code = NEW.lang
+    ON CONFLICT (key, lang) DO UPDATE SET document = EXCLUDED.document;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER resource_search_update
+AFTER INSERT OR UPDATE OF value ON resource_values
+FOR EACH ROW EXECUTE FUNCTION resource_search_update();
+
+CREATE FUNCTION resource_search_reindex() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE resource_search s
+    SET document = to_tsvector(NEW.search_config::regconfig, v.value)
+    FROM resource_values v
+    WHERE v.key = s.key AND v.lang = s.lang AND s.lang = NEW.code;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER resource_search_reindex
+AFTER UPDATE OF search_config ON languages
+FOR EACH ROW EXECUTE FUNCTION resource_search_reindex();
+
+INSERT INTO resource_search (key, lang, document)
+SELECT v.key, v.lang, to_tsvector(l.search_config::regconfig, v.value)
+FROM resource_values v
+JOIN languages l ON l.code = v.lang;
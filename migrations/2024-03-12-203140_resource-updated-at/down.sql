@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER resource_drafts_touch ON resource_drafts;
+DROP TRIGGER resource_values_touch ON resource_values;
+DROP FUNCTION resource_touch;
+
+ALTER TABLE resources
+DROP COLUMN updated_at;
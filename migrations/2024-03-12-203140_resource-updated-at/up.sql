@@ -0,0 +1,19 @@
+-- Your SQL goes here
+ALTER TABLE resources
+ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT current_timestamp;
+
+CREATE FUNCTION resource_touch() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE resources SET updated_at = clock_timestamp()
+    WHERE key = COALESCE(NEW.key, OLD.key);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER resource_values_touch
+AFTER INSERT OR UPDATE OR DELETE ON resource_values
+FOR EACH ROW EXECUTE FUNCTION resource_touch();
+
+CREATE TRIGGER resource_drafts_touch
+AFTER INSERT OR UPDATE OR DELETE ON resource_drafts
+FOR EACH ROW EXECUTE FUNCTION resource_touch();
@@ -0,0 +1,22 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE resource_search
+DROP CONSTRAINT resource_search_key_lang_fkey,
+ADD CONSTRAINT resource_search_key_lang_fkey FOREIGN KEY (key, lang) REFERENCES resource_values (key, lang) ON DELETE CASCADE;
+
+ALTER TABLE resource_revisions
+DROP CONSTRAINT resource_revisions_key_fkey,
+ADD CONSTRAINT resource_revisions_key_fkey FOREIGN KEY (key) REFERENCES resources (key) ON DELETE CASCADE;
+
+ALTER TABLE resource_drafts
+DROP CONSTRAINT resource_drafts_key_fkey,
+ADD CONSTRAINT resource_drafts_key_fkey FOREIGN KEY (key) REFERENCES resources (key) ON DELETE CASCADE;
+
+ALTER TABLE resource_values
+DROP CONSTRAINT resource_values_key_fkey,
+ADD CONSTRAINT resource_values_key_fkey FOREIGN KEY (key) REFERENCES resources (key) ON DELETE CASCADE;
+
+ALTER TABLE resources
+DROP COLUMN max_length,
+DROP COLUMN content_type,
+DROP COLUMN owner,
+DROP COLUMN description;
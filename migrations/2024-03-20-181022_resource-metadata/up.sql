@@ -0,0 +1,23 @@
+-- Your SQL goes here
+ALTER TABLE resources
+ADD COLUMN description TEXT NULL,
+ADD COLUMN owner INTEGER NULL REFERENCES users (id) ON DELETE SET NULL,
+ADD COLUMN content_type INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN max_length INTEGER NULL CHECK (max_length > 0);
+
+-- Renaming a key carries its values, drafts and history along
+ALTER TABLE resource_values
+DROP CONSTRAINT resource_values_key_fkey,
+ADD CONSTRAINT resource_values_key_fkey FOREIGN KEY (key) REFERENCES resources (key) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE resource_drafts
+DROP CONSTRAINT resource_drafts_key_fkey,
+ADD CONSTRAINT resource_drafts_key_fkey FOREIGN KEY (key) REFERENCES resources (key) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE resource_revisions
+DROP CONSTRAINT resource_revisions_key_fkey,
+ADD CONSTRAINT resource_revisions_key_fkey FOREIGN KEY (key) REFERENCES resources (key) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE resource_search
+DROP CONSTRAINT resource_search_key_lang_fkey,
+ADD CONSTRAINT resource_search_key_lang_fkey FOREIGN KEY (key, lang) REFERENCES resource_values (key, lang) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX resources_deleted_at_idx;
+
+ALTER TABLE resources
+DROP COLUMN deleted_by,
+DROP COLUMN deleted_at;
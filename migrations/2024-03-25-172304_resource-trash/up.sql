@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE resources
+ADD COLUMN deleted_at TIMESTAMP NULL,
+ADD COLUMN deleted_by INTEGER NULL REFERENCES users (id) ON DELETE SET NULL;
+
+CREATE INDEX resources_deleted_at_idx ON resources (deleted_at) WHERE deleted_at IS NOT NULL;
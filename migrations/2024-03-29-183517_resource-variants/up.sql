@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE resource_variants (
+    id SERIAL PRIMARY KEY,
+    key VARCHAR(64) NOT NULL REFERENCES resources (key) ON DELETE CASCADE ON UPDATE CASCADE,
+    lang VARCHAR(16) NOT NULL REFERENCES languages (code),
+    value TEXT NOT NULL,
+    valid_from TIMESTAMP NULL,
+    valid_until TIMESTAMP NULL,
+    author INTEGER NULL REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    CHECK (valid_from IS NOT NULL OR valid_until IS NOT NULL),
+    CHECK (valid_from < valid_until)
+);
+
+CREATE INDEX resource_variants_key_idx ON resource_variants (key, lang, valid_from);
+
+CREATE TRIGGER resource_variants_touch
+AFTER INSERT OR UPDATE OR DELETE ON resource_variants
+FOR EACH ROW EXECUTE FUNCTION resource_touch();
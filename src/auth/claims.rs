@@ -1,8 +1,10 @@
 use super::token::validate_token;
 use crate::{
     models::{role::Role, user::User},
+    repositories::user::repo::UserRepo,
     Secrets,
 };
+use deref_derive::Deref;
 use petompp_web_models::error::AuthError;
 use rocket::{http::Status, outcome::Outcome, request::FromRequest, Request};
 use std::{collections::BTreeMap, str::FromStr};
@@ -86,10 +88,25 @@ impl<'r> FromRequest<'r> for Claims {
         let Ok(claims) = validate_token(secrets, token) else {
             return Outcome::Failure((Status::Unauthorized, ()));
         };
-        Outcome::Success(claims)
+        // Tokens of deleted users are revoked and the ones of suspended users are not honored until the suspension is over
+        let Outcome::Success(pool) = request.guard::<&dyn UserRepo>().await else {
+            return Outcome::Failure((Status::InternalServerError, ()));
+        };
+        match pool.get_by_id(claims.sub) {
+            Ok(Some(user)) if user.deleted_at.is_some() => {
+                Outcome::Failure((Status::Unauthorized, ()))
+            }
+            Ok(Some(user)) if user.suspended_until().is_some() => {
+                Outcome::Failure((Status::Forbidden, ()))
+            }
+            Ok(Some(_)) => Outcome::Success(claims),
+            Ok(None) => Outcome::Failure((Status::Unauthorized, ())),
+            Err(_) => Outcome::Failure((Status::InternalServerError, ())),
+        }
     }
 }
 
+#[derive(Deref)]
 pub struct AdminClaims(Claims);
 
 #[rocket::async_trait]
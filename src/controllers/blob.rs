@@ -1,5 +1,8 @@
 use super::controller::Controller;
-use crate::{auth::claims::AdminClaims, services::azure_blob::AzureBlobService};
+use crate::{
+    auth::claims::AdminClaims,
+    services::azure_blob::{AzureBlobService, UPLOADED_BY_METADATA},
+};
 use azure_storage_blobs::prelude::ClientBuilder;
 use petompp_web_models::{
     error::ApiError,
@@ -101,14 +104,19 @@ struct BlobUploadForm<'a> {
 
 #[post("/<container>", data = "<value>")]
 async fn create_or_update<'a>(
-    _admin_claims: AdminClaims,
+    admin_claims: AdminClaims,
     container: &'a str,
     blob_service: &'a State<ClientBuilder>,
     value: Form<BlobUploadForm<'a>>,
 ) -> Result<Json<ApiResponse<'a, String>>, ApiError<'a>> {
+    let mut meta = value.meta.clone();
+    meta.metadata.insert(
+        UPLOADED_BY_METADATA.to_string(),
+        admin_claims.sub.to_string(),
+    );
     Ok(Json(ApiResponse::ok(
         blob_service
-            .create_or_update(container.to_string(), value.meta.clone(), value.content)
+            .create_or_update(container.to_string(), meta, value.content)
             .await?,
     )))
 }
@@ -0,0 +1,79 @@
+use super::controller::Controller;
+use crate::{
+    auth::claims::AdminClaims,
+    models::invitation::{Invitation, InvitationDto},
+    repositories::{invitation::repo::InvitationRepo, query_config::QueryConfig},
+};
+use petompp_web_models::{
+    error::{ApiError, Error},
+    models::api_response::ApiResponse,
+};
+use rocket::{delete, get, http::Status, post, routes, serde::json::Json};
+
+pub struct InvitationsController;
+
+impl Controller for InvitationsController {
+    fn path(&self) -> &'static str {
+        "/invitations"
+    }
+
+    fn routes(&self) -> Vec<rocket::Route> {
+        routes![get_all, create, delete]
+    }
+}
+
+#[get("/?<query..>")]
+async fn get_all(
+    _claims: AdminClaims,
+    query: QueryConfig,
+    pool: &dyn InvitationRepo,
+) -> Result<Json<ApiResponse<Vec<Invitation>>>, ApiError> {
+    Ok(Json(ApiResponse::ok(pool.get_all(&query)?)))
+}
+
+#[post("/", data = "<invitation>")]
+async fn create(
+    claims: AdminClaims,
+    invitation: Json<InvitationDto>,
+    pool: &dyn InvitationRepo,
+) -> Result<Json<ApiResponse<Invitation>>, ApiError> {
+    let invitation = invitation.into_inner();
+    if invitation.max_uses.is_some_and(|m| m <= 0)
+        || invitation
+            .expires_at
+            .is_some_and(|e| e <= chrono::Utc::now().naive_utc())
+        || invitation
+            .code
+            .as_ref()
+            .is_some_and(|c| c.trim().is_empty() || c.len() > 64)
+    {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Invitation needs a positive use limit, a future expiry and a code of up to 64 characters"
+                .to_string(),
+        )
+        .into());
+    }
+    let invitation = Invitation {
+        code: invitation.code.unwrap_or_else(Invitation::generate_code),
+        max_uses: invitation.max_uses,
+        role: invitation.role,
+        auto_confirm: invitation.auto_confirm,
+        expires_at: invitation.expires_at,
+        created_by: Some(claims.sub),
+        ..Default::default()
+    };
+    Ok(Json(ApiResponse::ok(pool.create(&invitation)?)))
+}
+
+#[delete("/<code>")]
+async fn delete<'a>(
+    _claims: AdminClaims,
+    code: &'a str,
+    pool: &dyn InvitationRepo,
+) -> Result<Json<ApiResponse<'a, Invitation>>, ApiError<'a>> {
+    let invitation = pool
+        .delete(code)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(invitation)))
+}
@@ -0,0 +1,125 @@
+use super::controller::Controller;
+use crate::{
+    auth::claims::AdminClaims,
+    models::lang::{Lang, Language, LanguageDto, LanguageUpdate},
+    repositories::language::repo::LanguageRepo,
+};
+use petompp_web_models::{
+    error::{ApiError, Error},
+    models::api_response::ApiResponse,
+};
+use rocket::{delete, get, http::Status, post, routes, serde::json::Json};
+
+pub struct LanguagesController;
+
+impl Controller for LanguagesController {
+    fn path(&self) -> &'static str {
+        "/languages"
+    }
+
+    fn routes(&self) -> Vec<rocket::Route> {
+        routes![get_all, create, update, delete]
+    }
+}
+
+#[get("/")]
+async fn get_all(pool: &dyn LanguageRepo) -> Result<Json<ApiResponse<Vec<Language>>>, ApiError> {
+    Ok(Json(ApiResponse::ok(pool.get_all()?)))
+}
+
+#[post("/", data = "<language>")]
+async fn create(
+    _claims: AdminClaims,
+    language: Json<LanguageDto>,
+    pool: &dyn LanguageRepo,
+) -> Result<Json<ApiResponse<Language>>, ApiError> {
+    let LanguageDto {
+        code,
+        name,
+        fallback,
+        search_config,
+    } = language.into_inner();
+    let name = name.trim().to_string();
+    let (Some(code), false) = (Lang::new(&code), name.is_empty() || name.len() > 64) else {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Language needs a code like `de` or `pt-br` and a name of up to 64 characters"
+                .to_string(),
+        )
+        .into());
+    };
+    let language = Language {
+        fallback: validate_fallback(&code, fallback)?,
+        code: code.to_string(),
+        name,
+        search_config,
+        ..Default::default()
+    };
+    Ok(Json(ApiResponse::ok(pool.create(&language)?)))
+}
+
+#[post("/<code>", data = "<language>")]
+async fn update<'a>(
+    _claims: AdminClaims,
+    code: &'a str,
+    language: Json<LanguageUpdate>,
+    pool: &dyn LanguageRepo,
+) -> Result<Json<ApiResponse<'a, Language>>, ApiError<'a>> {
+    let LanguageUpdate {
+        name,
+        fallback,
+        search_config,
+    } = language.into_inner();
+    let name = name.trim().to_string();
+    let (Some(code), false) = (Lang::new(code), name.is_empty() || name.len() > 64) else {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Language needs a name of up to 64 characters".to_string(),
+        )
+        .into());
+    };
+    let language = Language {
+        fallback: validate_fallback(&code, fallback)?,
+        code: code.to_string(),
+        name,
+        search_config,
+        ..Default::default()
+    };
+    let language = pool
+        .update(&language)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(language)))
+}
+
+/// The base language ends every chain, so it cannot fall back to anything.
+fn validate_fallback(code: &Lang, fallback: Option<String>) -> Result<Option<String>, Error> {
+    let Some(fallback) = fallback else {
+        return Ok(None);
+    };
+    match Lang::new(&fallback) {
+        Some(fallback) if !code.is_base() && &fallback != code => Ok(Some(fallback.to_string())),
+        _ => Err(Error::Status(
+            Status::BadRequest.code,
+            format!("Invalid fallback language for {}", code),
+        )),
+    }
+}
+
+#[delete("/<code>")]
+async fn delete<'a>(
+    _claims: AdminClaims,
+    code: &'a str,
+    pool: &dyn LanguageRepo,
+) -> Result<Json<ApiResponse<'a, Language>>, ApiError<'a>> {
+    if Lang::new(code).is_some_and(|l| l.is_base()) {
+        return Err(Error::Status(
+            Status::Conflict.code,
+            "The base language cannot be deleted".to_string(),
+        )
+        .into());
+    }
+    let language = pool
+        .delete(code)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(language)))
+}
@@ -1,6 +1,10 @@
 pub mod blob;
 pub mod controller;
 pub mod health;
+pub mod invitations;
+pub mod languages;
+pub mod registrations;
+pub mod reserved_names;
 pub mod resources;
 pub mod user_settings;
 pub mod users;
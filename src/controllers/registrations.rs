@@ -0,0 +1,73 @@
+use super::controller::Controller;
+use crate::{
+    auth::claims::AdminClaims,
+    models::registration::{Registration, RegistrationReview, RegistrationStatus},
+    repositories::{query_config::QueryConfig, registration::repo::RegistrationRepo},
+};
+use petompp_web_models::{
+    error::{ApiError, Error},
+    models::api_response::ApiResponse,
+};
+use rocket::{get, http::Status, post, routes, serde::json::Json};
+
+pub struct RegistrationsController;
+
+impl Controller for RegistrationsController {
+    fn path(&self) -> &'static str {
+        "/users/registrations"
+    }
+
+    fn routes(&self) -> Vec<rocket::Route> {
+        routes![get_all, approve, reject]
+    }
+}
+
+#[get("/?<status>&<query..>")]
+async fn get_all(
+    _claims: AdminClaims,
+    status: Option<RegistrationStatus>,
+    query: QueryConfig,
+    pool: &dyn RegistrationRepo,
+) -> Result<Json<ApiResponse<Vec<Registration>>>, ApiError> {
+    Ok(Json(ApiResponse::ok(
+        pool.get_all(status.unwrap_or_default(), &query)?,
+    )))
+}
+
+#[post("/<id>/approve", data = "<review>")]
+async fn approve(
+    claims: AdminClaims,
+    id: i32,
+    review: Json<RegistrationReview>,
+    pool: &dyn RegistrationRepo,
+) -> Result<Json<ApiResponse<Registration>>, ApiError> {
+    let registration = pool
+        .review(
+            id,
+            RegistrationStatus::Approved,
+            claims.sub,
+            review.into_inner().reason,
+        )?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(registration)))
+}
+
+#[post("/<id>/reject", data = "<review>")]
+async fn reject(
+    claims: AdminClaims,
+    id: i32,
+    review: Json<RegistrationReview>,
+    pool: &dyn RegistrationRepo,
+) -> Result<Json<ApiResponse<Registration>>, ApiError> {
+    let Some(reason) = review.into_inner().reason.filter(|r| !r.trim().is_empty()) else {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Rejecting a registration requires a reason".to_string(),
+        )
+        .into());
+    };
+    let registration = pool
+        .review(id, RegistrationStatus::Rejected, claims.sub, Some(reason))?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(registration)))
+}
@@ -0,0 +1,77 @@
+use super::controller::Controller;
+use crate::{
+    auth::claims::AdminClaims,
+    models::reserved_name::{ReservedName, ReservedNameDto, ReservedNameKind},
+    repositories::{query_config::QueryConfig, reserved_name::repo::ReservedNameRepo},
+};
+use petompp_web_models::{
+    error::{ApiError, Error},
+    models::api_response::ApiResponse,
+};
+use regex::Regex;
+use rocket::{delete, get, http::Status, post, routes, serde::json::Json};
+
+pub struct ReservedNamesController;
+
+impl Controller for ReservedNamesController {
+    fn path(&self) -> &'static str {
+        "/settings/users/reserved-names"
+    }
+
+    fn routes(&self) -> Vec<rocket::Route> {
+        routes![get_all, create, delete]
+    }
+}
+
+#[get("/?<query..>")]
+async fn get_all(
+    _claims: AdminClaims,
+    query: QueryConfig,
+    pool: &dyn ReservedNameRepo,
+) -> Result<Json<ApiResponse<Vec<ReservedName>>>, ApiError> {
+    Ok(Json(ApiResponse::ok(pool.get_all(&query)?)))
+}
+
+#[post("/", data = "<reserved_name>")]
+async fn create(
+    claims: AdminClaims,
+    reserved_name: Json<ReservedNameDto>,
+    pool: &dyn ReservedNameRepo,
+) -> Result<Json<ApiResponse<ReservedName>>, ApiError> {
+    let ReservedNameDto {
+        pattern,
+        kind,
+        reason,
+    } = reserved_name.into_inner();
+    let pattern = pattern.trim().to_string();
+    if pattern.is_empty() || pattern.len() > 255 {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Pattern must have between 1 and 255 characters".to_string(),
+        )
+        .into());
+    }
+    if kind == ReservedNameKind::Regex {
+        Regex::new(&pattern).map_err(|e| Error::Status(Status::BadRequest.code, e.to_string()))?;
+    }
+    let reserved_name = ReservedName {
+        pattern,
+        kind,
+        reason,
+        created_by: Some(claims.sub),
+        ..Default::default()
+    };
+    Ok(Json(ApiResponse::ok(pool.create(&reserved_name)?)))
+}
+
+#[delete("/<id>")]
+async fn delete(
+    _claims: AdminClaims,
+    id: i32,
+    pool: &dyn ReservedNameRepo,
+) -> Result<Json<ApiResponse<ReservedName>>, ApiError> {
+    let reserved_name = pool
+        .delete(id)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(reserved_name)))
+}
@@ -1,98 +1,802 @@
 use super::controller::Controller;
 use crate::{
-    auth::claims::AdminClaims, models::resource_data::Resource,
-    repositories::resources::repo::ResourcesRepo,
+    auth::claims::{AdminClaims, Claims},
+    models::{
+        accept_language::AcceptLanguage,
+        lang::Lang,
+        message_format::{Message, MessageArg},
+        preconditions::Preconditions,
+        resource_coverage::{CoverageItem, CoverageStatus, LanguageCoverage},
+        resource_data::{
+            RenameRequest, Resource, ResourceMetaDto, ResourceSelection, ResourceValue,
+            SearchResult, ServedValue, ValueFormat,
+        },
+        resource_draft::{PublishRequest, ResourceDraft},
+        resource_revision::{ResourceRevision, RevisionDiff},
+        resource_transfer::{ImportDiff, TransferFormat},
+        resource_variant::{ResourceVariant, ResourceVariantDto},
+        role::Role,
+    },
+    repositories::{
+        language::repo::LanguageRepo, query_config::QueryConfig,
+        resource_revision::repo::ResourceRevisionRepo, resource_variant::repo::ResourceVariantRepo,
+        resources::repo::ResourcesRepo, user_preferences::repo::UserPreferencesRepo,
+    },
+    services::{markdown::MarkdownRenderer, resource_transfer},
 };
 use petompp_web_models::{
     error::{ApiError, Error, ResourceDataValidationError, ValidationError},
-    models::{api_response::ApiResponse, country::Country, resource_data::ResourceData},
+    models::{api_response::ApiResponse, resource_data::ResourceData},
 };
-use rocket::{delete, get, post, put, routes, serde::json::Json};
+use rocket::{
+    data::{Data, Limits, ToByteUnit},
+    delete, get,
+    http::{ContentType, Header, Status},
+    post, put, routes,
+    serde::json::Json,
+    Responder, State,
+};
+use std::collections::BTreeMap;
 
 pub struct ResourcesController;
 
+const MAX_BATCH_KEYS: usize = 100;
+
 impl Controller for ResourcesController {
     fn path(&self) -> &'static str {
         "/res"
     }
 
     fn routes(&self) -> Vec<rocket::Route> {
-        routes![get, get_all_keys, create, update, delete, delete_lang]
+        routes![
+            get,
+            get_many,
+            get_all_keys,
+            get_namespaces,
+            search,
+            get_coverage,
+            get_coverage_items,
+            get_values,
+            get_meta,
+            update_meta,
+            rename,
+            format_message,
+            get_drafts,
+            publish,
+            discard_drafts,
+            export,
+            import,
+            get_revisions,
+            get_revision_diff,
+            restore_revision,
+            get_variants,
+            create_variant,
+            update_variant,
+            delete_variant,
+            create,
+            update,
+            update_lang,
+            delete,
+            delete_lang,
+            get_trash,
+            restore_from_trash,
+            purge
+        ]
+    }
+}
+
+/// Resource value with the language it was served in and whether it came from a fallback language.
+///
+/// The body keeps the `(lang, value)` shape, so the fallback is only reported in headers.
+#[derive(Responder)]
+struct ServedResponse<'a> {
+    inner: Json<ApiResponse<'a, (Lang, String)>>,
+    content_language: Header<'static>,
+    fallback: Header<'static>,
+}
+
+impl<'a> From<ServedValue> for ServedResponse<'a> {
+    fn from(value: ServedValue) -> Self {
+        Self {
+            content_language: Header::new("Content-Language", value.lang.to_string()),
+            fallback: Header::new("X-Resource-Fallback", value.fallback.to_string()),
+            inner: Json(ApiResponse::ok((value.lang, value.value))),
+        }
+    }
+}
+
+/// Response that depends on the negotiated language.
+#[derive(Responder)]
+struct Negotiated<R> {
+    inner: R,
+    vary: Header<'static>,
+}
+
+impl<R> Negotiated<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            vary: Header::new("Vary", "Accept-Language"),
+        }
+    }
+}
+
+/// Response carrying the version of the resource it was built from.
+#[derive(Responder)]
+struct Tagged<R> {
+    inner: R,
+    etag: Header<'static>,
+}
+
+impl<R> Tagged<R> {
+    fn new(inner: R, updated_at: &chrono::NaiveDateTime) -> Self {
+        Self {
+            inner,
+            etag: Header::new("ETag", Preconditions::etag(updated_at, None)),
+        }
     }
 }
 
-#[get("/<key>?<lang>")]
+/// Response to a conditional fetch, a bare `304 Not Modified` when the client's copy is current.
+#[derive(Responder)]
+enum Cached<R> {
+    Modified(R, Header<'static>, Header<'static>, Header<'static>),
+    NotModified(Status, Header<'static>),
+}
+
+#[get("/<key>?<lang>&<draft>&<format>")]
+#[allow(clippy::too_many_arguments)]
 async fn get<'a>(
     key: &'a str,
-    lang: Country,
+    lang: Option<Lang>,
+    draft: Option<bool>,
+    format: Option<ValueFormat>,
+    claims: Option<Claims>,
+    accept_language: AcceptLanguage,
+    preconditions: Preconditions,
+    pool: &dyn ResourcesRepo,
+    preferences_pool: &dyn UserPreferencesRepo,
+    language_pool: &dyn LanguageRepo,
+    variant_pool: &dyn ResourceVariantRepo,
+    renderer: &State<MarkdownRenderer>,
+) -> Result<Negotiated<Cached<ServedResponse<'a>>>, ApiError<'a>> {
+    // Drafts are only visible to admins, other users are known but not allowed
+    let draft = draft.unwrap_or_default();
+    let denied = match &claims {
+        _ if !draft => None,
+        None => Some(Status::Unauthorized),
+        Some(claims) if claims.acs != Role::Admin => Some(Status::Forbidden),
+        Some(_) => None,
+    };
+    if let Some(status) = denied {
+        return Err(Error::Status(status.code, status.to_string()).into());
+    }
+    let lang = resolve_lang(
+        lang,
+        claims,
+        &accept_language,
+        preferences_pool,
+        language_pool,
+    )?;
+    let format = format.unwrap_or_default();
+    let updated_at = updated_at(pool, key)?;
+    // Drafts are never cached, their tag is the version to send back in `If-Match`
+    if draft {
+        return Ok(Negotiated::new(Cached::Modified(
+            apply_format(pool.get(key, &lang, true)?, format, renderer).into(),
+            Header::new("ETag", Preconditions::etag(&updated_at, None)),
+            Header::new("Last-Modified", Preconditions::last_modified(&updated_at)),
+            Header::new("Cache-Control", "no-store"),
+        )));
+    }
+    // Variants come and go without changing the resource, so the served one is a part of the tag
+    // and the served value was last modified when a window last opened or closed, if that was later
+    let modified_at = variant_pool
+        .last_boundary(key, chrono::Utc::now().naive_utc())?
+        .map_or(updated_at, |boundary| boundary.max(updated_at));
+    let value = pool.get(key, &lang, false)?;
+    let mut variant = match format {
+        ValueFormat::Markdown => lang.to_string(),
+        ValueFormat::Html => format!("{}-html", lang),
+    };
+    if let Some(id) = value.variant {
+        variant = format!("{}-v{}", variant, id);
+    }
+    let etag = Preconditions::etag(&updated_at, Some(&variant));
+    if preconditions.not_modified(&etag, &modified_at) {
+        return Ok(Negotiated::new(Cached::NotModified(
+            Status::NotModified,
+            Header::new("ETag", etag),
+        )));
+    }
+    Ok(Negotiated::new(Cached::Modified(
+        apply_format(value, format, renderer).into(),
+        Header::new("ETag", etag),
+        Header::new("Last-Modified", Preconditions::last_modified(&modified_at)),
+        Header::new("Cache-Control", "no-cache"),
+    )))
+}
+
+fn apply_format(
+    mut value: ServedValue,
+    format: ValueFormat,
+    renderer: &MarkdownRenderer,
+) -> ServedValue {
+    if format == ValueFormat::Html {
+        value.value = renderer.render(&value.value, value.content_type);
+    }
+    value
+}
+
+fn updated_at(pool: &dyn ResourcesRepo, key: &str) -> Result<chrono::NaiveDateTime, Error> {
+    pool.get_resource(key)?
+        .and_then(|r| r.updated_at)
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))
+}
+
+#[get("/?<keys>&<prefix>&<lang>&<format>")]
+#[allow(clippy::too_many_arguments)]
+async fn get_many<'a>(
+    keys: Option<&'a str>,
+    prefix: Option<&'a str>,
+    lang: Option<Lang>,
+    format: Option<ValueFormat>,
+    claims: Option<Claims>,
+    accept_language: AcceptLanguage,
     pool: &dyn ResourcesRepo,
-) -> Result<Json<ApiResponse<'a, (Country, String)>>, ApiError<'a>> {
-    Ok(Json(ApiResponse::ok(pool.get(key, &lang)?)))
+    preferences_pool: &dyn UserPreferencesRepo,
+    language_pool: &dyn LanguageRepo,
+    renderer: &State<MarkdownRenderer>,
+) -> Result<Negotiated<Json<ApiResponse<'a, BTreeMap<String, ServedValue>>>>, ApiError<'a>> {
+    let selection = match (keys, prefix) {
+        (Some(keys), None) => {
+            let keys = keys
+                .split(',')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect::<Vec<_>>();
+            if keys.is_empty() || keys.len() > MAX_BATCH_KEYS {
+                return Err(Error::Status(
+                    Status::BadRequest.code,
+                    format!("Specify between 1 and {} keys", MAX_BATCH_KEYS),
+                )
+                .into());
+            }
+            ResourceSelection::Keys(keys)
+        }
+        (None, Some(prefix)) if !prefix.is_empty() => ResourceSelection::Prefix(prefix.to_string()),
+        _ => {
+            return Err(Error::Status(
+                Status::BadRequest.code,
+                "Specify either a list of keys or a prefix".to_string(),
+            )
+            .into())
+        }
+    };
+    let lang = resolve_lang(
+        lang,
+        claims,
+        &accept_language,
+        preferences_pool,
+        language_pool,
+    )?;
+    let format = format.unwrap_or_default();
+    let values = pool
+        .get_many(&selection, &lang)?
+        .into_iter()
+        .map(|(key, value)| (key, apply_format(value, format, renderer)))
+        .collect();
+    Ok(Negotiated::new(Json(ApiResponse::ok(values))))
+}
+
+/// Picks the requested language, then the caller's preferred one, then the best match
+/// for `Accept-Language` and finally the base language.
+fn resolve_lang(
+    lang: Option<Lang>,
+    claims: Option<Claims>,
+    accept_language: &AcceptLanguage,
+    preferences_pool: &dyn UserPreferencesRepo,
+    language_pool: &dyn LanguageRepo,
+) -> Result<Lang, Error> {
+    if let Some(lang) = lang {
+        return Ok(lang);
+    }
+    if let Some(lang) = match claims {
+        Some(claims) => preferences_pool.get(claims.sub)?.lang,
+        None => None,
+    } {
+        return Ok(lang);
+    }
+    let supported = language_pool
+        .get_all()?
+        .into_iter()
+        .filter_map(|l| Lang::new(&l.code))
+        .collect::<Vec<_>>();
+    Ok(accept_language
+        .negotiate(&supported)
+        .unwrap_or_else(Lang::base))
 }
 
-#[get("/keys")]
+#[get("/keys?<query..>")]
 async fn get_all_keys<'a>(
+    query: QueryConfig,
     pool: &dyn ResourcesRepo,
 ) -> Result<Json<ApiResponse<'a, Vec<String>>>, ApiError<'a>> {
     Ok(Json(ApiResponse::ok(
-        pool.get_all()?
+        pool.get_all(&query)?
             .iter()
-            .map(|x| x.key.clone().unwrap())
+            .map(|x| x.key.clone())
+            .collect(),
+    )))
+}
+
+/// Keys with their metadata grouped by namespace.
+#[get("/namespaces?<query..>")]
+async fn get_namespaces<'a>(
+    _admin_claims: AdminClaims,
+    query: QueryConfig,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, BTreeMap<String, Vec<Resource>>>>, ApiError<'a>> {
+    let mut namespaces = BTreeMap::<String, Vec<Resource>>::new();
+    for resource in pool.get_all(&query)? {
+        namespaces
+            .entry(resource.namespace().to_string())
+            .or_default()
+            .push(resource);
+    }
+    Ok(Json(ApiResponse::ok(namespaces)))
+}
+
+#[get("/search?<q>&<lang>&<query..>")]
+async fn search<'a>(
+    q: &'a str,
+    lang: Option<Lang>,
+    query: QueryConfig,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Vec<SearchResult>>>, ApiError<'a>> {
+    if q.trim().is_empty() {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Search text must not be empty".to_string(),
+        )
+        .into());
+    }
+    Ok(Json(ApiResponse::ok(pool.search(
+        q,
+        lang.as_ref(),
+        &query,
+    )?)))
+}
+
+#[get("/coverage?<prefix>&<lang>")]
+async fn get_coverage<'a>(
+    _admin_claims: AdminClaims,
+    prefix: Option<&'a str>,
+    lang: Vec<Lang>,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Vec<LanguageCoverage>>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(pool.coverage(prefix, &lang)?)))
+}
+
+/// Values that need translating, both missing and outdated ones unless `status` is given.
+#[get("/coverage/items?<prefix>&<lang>&<status>")]
+async fn get_coverage_items<'a>(
+    _admin_claims: AdminClaims,
+    prefix: Option<&'a str>,
+    lang: Vec<Lang>,
+    status: Option<CoverageStatus>,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Vec<CoverageItem>>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(
+        pool.coverage(prefix, &lang)?
+            .into_iter()
+            .flat_map(LanguageCoverage::items)
+            .filter(|i| status.is_none_or(|s| i.status == s))
             .collect(),
     )))
 }
 
+#[get("/<key>/values")]
+async fn get_values<'a>(
+    key: &'a str,
+    pool: &dyn ResourcesRepo,
+) -> Result<Tagged<Json<ApiResponse<'a, Vec<ResourceValue>>>>, ApiError<'a>> {
+    let updated_at = updated_at(pool, key)?;
+    Ok(Tagged::new(
+        Json(ApiResponse::ok(pool.get_values(key)?)),
+        &updated_at,
+    ))
+}
+
+#[get("/<key>/meta")]
+async fn get_meta<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, ResourceMetaDto>>, ApiError<'a>> {
+    let resource = pool
+        .get_resource(key)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(resource.into())))
+}
+
+#[put("/<key>/meta", data = "<meta>")]
+async fn update_meta<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    meta: Json<ResourceMetaDto>,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, ResourceMetaDto>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(pool.update_meta(key, &meta)?.into())))
+}
+
+/// Moves the resource to a new key, its values, drafts and history go along.
+#[post("/<key>/rename", data = "<request>")]
+async fn rename<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    request: Json<RenameRequest>,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Resource>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(pool.rename(key, request.key.trim())?)))
+}
+
+/// Formats the resource with the arguments and the plural rules of the served language.
+#[post("/<key>/format?<lang>", data = "<args>")]
+#[allow(clippy::too_many_arguments)]
+async fn format_message<'a>(
+    key: &'a str,
+    lang: Option<Lang>,
+    args: Json<BTreeMap<String, MessageArg>>,
+    claims: Option<Claims>,
+    accept_language: AcceptLanguage,
+    pool: &dyn ResourcesRepo,
+    preferences_pool: &dyn UserPreferencesRepo,
+    language_pool: &dyn LanguageRepo,
+) -> Result<Negotiated<ServedResponse<'a>>, ApiError<'a>> {
+    let lang = resolve_lang(
+        lang,
+        claims,
+        &accept_language,
+        preferences_pool,
+        language_pool,
+    )?;
+    let mut value = pool.get(key, &lang, false)?;
+    value.value = Message::parse(&value.value)
+        .and_then(|m| m.format(&value.lang, &args))
+        .map_err(|e| Error::Status(Status::BadRequest.code, e.to_string()))?;
+    Ok(Negotiated::new(value.into()))
+}
+
+#[get("/<key>/drafts")]
+async fn get_drafts<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Vec<ResourceDraft>>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(pool.get_drafts(key)?)))
+}
+
+#[post("/<key>/publish", data = "<request>")]
+async fn publish<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    request: Json<PublishRequest>,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Vec<ResourceDraft>>>, ApiError<'a>> {
+    let request = request.into_inner();
+    Ok(Json(ApiResponse::ok(pool.publish(
+        key,
+        &request.langs,
+        request.publish_at,
+    )?)))
+}
+
+#[delete("/<key>/drafts?<lang>")]
+async fn discard_drafts<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    lang: Vec<Lang>,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, &'a str>>, ApiError<'a>> {
+    pool.discard_drafts(key, &lang)?;
+    Ok(Json(ApiResponse::ok("ok")))
+}
+
+/// Exported resources as a downloadable file.
+#[derive(Responder)]
+struct TransferFile {
+    inner: (ContentType, String),
+    disposition: Header<'static>,
+}
+
+#[get("/export?<format>&<prefix>&<lang>")]
+async fn export<'a>(
+    _admin_claims: AdminClaims,
+    format: Option<TransferFormat>,
+    prefix: Option<&'a str>,
+    lang: Vec<Lang>,
+    pool: &dyn ResourcesRepo,
+    language_pool: &dyn LanguageRepo,
+) -> Result<TransferFile, ApiError<'a>> {
+    let format = format.unwrap_or_default();
+    let langs = match lang.is_empty() {
+        true => language_pool
+            .get_all()?
+            .into_iter()
+            .filter_map(|l| Lang::new(&l.code))
+            .collect(),
+        false => lang,
+    };
+    // Bilingual formats carry the base value as the source text
+    let mut query_langs = langs.clone();
+    if format.is_bilingual() {
+        query_langs.push(Lang::base());
+    }
+    let values = pool.get_values_in(prefix, &query_langs)?;
+    let (top, sub) = format.content_type();
+    Ok(TransferFile {
+        inner: (
+            ContentType::new(top, sub),
+            resource_transfer::export(format, &values, &langs)?,
+        ),
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"resources.{}\"", format.extension()),
+        ),
+    })
+}
+
+#[post("/import?<format>&<prefix>&<lang>&<apply>", data = "<data>")]
+#[allow(clippy::too_many_arguments)]
+async fn import<'a>(
+    admin_claims: AdminClaims,
+    format: Option<TransferFormat>,
+    prefix: Option<&'a str>,
+    lang: Vec<Lang>,
+    apply: Option<bool>,
+    data: Data<'a>,
+    limits: &'a Limits,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, ImportDiff>>, ApiError<'a>> {
+    let format = format.unwrap_or_default();
+    if format.is_bilingual() && lang.len() > 1 {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Specify a single target language".to_string(),
+        )
+        .into());
+    }
+    let content = data
+        .open(limits.get("import").unwrap_or(2.mebibytes()))
+        .into_string()
+        .await
+        .map_err(|e| Error::Status(Status::BadRequest.code, e.to_string()))?;
+    if !content.is_complete() {
+        return Err(Error::Status(
+            Status::PayloadTooLarge.code,
+            Status::PayloadTooLarge.to_string(),
+        )
+        .into());
+    }
+    let imported = resource_transfer::import(format, &content, lang.first())?;
+    // Without explicit languages the import covers the ones present in the file
+    let langs = match lang.is_empty() || format.is_bilingual() {
+        true => imported.langs,
+        false => lang,
+    };
+    Ok(Json(ApiResponse::ok(pool.import(
+        &imported.values,
+        prefix,
+        &langs,
+        apply.unwrap_or_default(),
+        admin_claims.sub,
+    )?)))
+}
+
+#[get("/<key>/revisions?<lang>&<query..>")]
+async fn get_revisions<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    lang: Option<Lang>,
+    query: QueryConfig,
+    pool: &dyn ResourceRevisionRepo,
+) -> Result<Json<ApiResponse<'a, Vec<ResourceRevision>>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(pool.get_all(
+        key,
+        lang.as_ref(),
+        &query,
+    )?)))
+}
+
+#[get("/<key>/revisions/diff?<from>&<to>")]
+async fn get_revision_diff<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    from: i32,
+    to: i32,
+    pool: &dyn ResourceRevisionRepo,
+) -> Result<Json<ApiResponse<'a, RevisionDiff>>, ApiError<'a>> {
+    let (Some(from), Some(to)) = (pool.get(key, from)?, pool.get(key, to)?) else {
+        return Err(Error::Status(Status::NotFound.code, Status::NotFound.to_string()).into());
+    };
+    Ok(Json(ApiResponse::ok(RevisionDiff::new(&from, &to))))
+}
+
+#[post("/<key>/revisions/<id>/restore")]
+async fn restore_revision<'a>(
+    admin_claims: AdminClaims,
+    key: &'a str,
+    id: i32,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Vec<ResourceValue>>>, ApiError<'a>> {
+    let values = pool
+        .restore(key, id, admin_claims.sub)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(values)))
+}
+
+#[get("/<key>/variants")]
+async fn get_variants<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    pool: &dyn ResourceVariantRepo,
+) -> Result<Json<ApiResponse<'a, Vec<ResourceVariant>>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(pool.get_all(key)?)))
+}
+
+/// Adds a value to serve instead of the published one during its window.
+#[post("/<key>/variants", data = "<variant>")]
+async fn create_variant<'a>(
+    admin_claims: AdminClaims,
+    key: &'a str,
+    variant: Json<ResourceVariantDto>,
+    pool: &dyn ResourceVariantRepo,
+) -> Result<Json<ApiResponse<'a, ResourceVariant>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(pool.create(
+        key,
+        variant.into_inner(),
+        admin_claims.sub,
+    )?)))
+}
+
+#[put("/<key>/variants/<id>", data = "<variant>")]
+async fn update_variant<'a>(
+    admin_claims: AdminClaims,
+    key: &'a str,
+    id: i32,
+    variant: Json<ResourceVariantDto>,
+    pool: &dyn ResourceVariantRepo,
+) -> Result<Json<ApiResponse<'a, ResourceVariant>>, ApiError<'a>> {
+    let variant = pool
+        .update(key, id, variant.into_inner(), admin_claims.sub)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(variant)))
+}
+
+#[delete("/<key>/variants/<id>")]
+async fn delete_variant<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    id: i32,
+    pool: &dyn ResourceVariantRepo,
+) -> Result<Json<ApiResponse<'a, &'a str>>, ApiError<'a>> {
+    if pool.delete(key, id)? == 0 {
+        return Err(Error::Status(Status::NotFound.code, Status::NotFound.to_string()).into());
+    }
+    Ok(Json(ApiResponse::ok("ok")))
+}
+
 #[put("/<key>", data = "<value>")]
 async fn create<'a>(
-    _admin_claims: AdminClaims,
+    admin_claims: AdminClaims,
     key: &'a str,
     value: Json<ResourceData>,
     pool: &dyn ResourcesRepo,
 ) -> Result<Json<ApiResponse<'a, ResourceData>>, ApiError<'a>> {
-    let value = Resource {
-        key: Some(key.to_string()),
-        ..value.into_inner().into()
-    };
-    Ok(Json(ApiResponse::ok(pool.create(&value)?.into())))
+    let values = ResourceValue::from_data(ResourceData {
+        key: key.to_string(),
+        ..value.into_inner()
+    });
+    Ok(Json(ApiResponse::ok(ResourceValue::into_data(
+        key.to_string(),
+        pool.create(key, &values, admin_claims.sub)?,
+    ))))
 }
 
 #[post("/<key>", data = "<value>")]
 async fn update<'a>(
-    _admin_claims: AdminClaims,
+    admin_claims: AdminClaims,
     key: &'a str,
     value: Json<ResourceData>,
+    preconditions: Preconditions,
     pool: &dyn ResourcesRepo,
-) -> Result<Json<ApiResponse<'a, ResourceData>>, ApiError<'a>> {
+) -> Result<Tagged<Json<ApiResponse<'a, ResourceData>>>, ApiError<'a>> {
     if key != value.key.as_str() {
         return Err(Error::Validation(ValidationError::ResourceData(
             ResourceDataValidationError::KeyMismatch(key.to_string(), value.key.clone()),
         ))
         .into());
     }
-    let value = Resource {
-        key: Some(key.to_string()),
-        ..value.into_inner().into()
-    };
-    Ok(Json(ApiResponse::ok(pool.update(&value)?.into())))
+    let values = ResourceValue::from_data(value.into_inner());
+    let (values, updated_at) = pool.update(key, &values, admin_claims.sub, &preconditions)?;
+    Ok(Tagged::new(
+        Json(ApiResponse::ok(ResourceValue::into_data(
+            key.to_string(),
+            values,
+        ))),
+        &updated_at,
+    ))
 }
 
+#[put("/<key>?<lang>", data = "<value>")]
+async fn update_lang<'a>(
+    admin_claims: AdminClaims,
+    key: &'a str,
+    lang: Lang,
+    value: Json<String>,
+    preconditions: Preconditions,
+    pool: &dyn ResourcesRepo,
+) -> Result<Tagged<Json<ApiResponse<'a, Vec<ResourceValue>>>>, ApiError<'a>> {
+    let value = ResourceValue::new(key, &lang, value.into_inner());
+    let (values, updated_at) = pool.update(key, &[value], admin_claims.sub, &preconditions)?;
+    Ok(Tagged::new(Json(ApiResponse::ok(values)), &updated_at))
+}
+
+/// Moves the resource to the trash, it can be restored until it is purged.
 #[delete("/<key>")]
 async fn delete<'a>(
-    _admin_claims: AdminClaims,
+    admin_claims: AdminClaims,
     key: &'a str,
     pool: &dyn ResourcesRepo,
-) -> Result<Json<ApiResponse<'a, &'a str>>, ApiError<'a>> {
-    pool.delete(key)?;
-    Ok(Json(ApiResponse::ok("ok")))
+) -> Result<Json<ApiResponse<'a, Resource>>, ApiError<'a>> {
+    let resource = pool
+        .delete(key, admin_claims.sub)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(resource)))
 }
 
 #[delete("/<key>?<lang>")]
 async fn delete_lang<'a>(
+    admin_claims: AdminClaims,
+    key: &'a str,
+    lang: Lang,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, &'a str>>, ApiError<'a>> {
+    pool.delete_lang(key, &lang, admin_claims.sub)?;
+    Ok(Json(ApiResponse::ok("ok")))
+}
+
+#[get("/trash?<query..>")]
+async fn get_trash<'a>(
+    _admin_claims: AdminClaims,
+    query: QueryConfig,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Vec<Resource>>>, ApiError<'a>> {
+    Ok(Json(ApiResponse::ok(pool.get_trash(&query)?)))
+}
+
+#[post("/trash/<key>/restore")]
+async fn restore_from_trash<'a>(
+    _admin_claims: AdminClaims,
+    key: &'a str,
+    pool: &dyn ResourcesRepo,
+) -> Result<Json<ApiResponse<'a, Resource>>, ApiError<'a>> {
+    let resource = pool
+        .restore_from_trash(key)?
+        .ok_or_else(|| Error::Status(Status::NotFound.code, Status::NotFound.to_string()))?;
+    Ok(Json(ApiResponse::ok(resource)))
+}
+
+/// Permanently removes a resource from the trash along with its values and history.
+#[delete("/trash/<key>")]
+async fn purge<'a>(
     _admin_claims: AdminClaims,
     key: &'a str,
-    lang: Country,
     pool: &dyn ResourcesRepo,
 ) -> Result<Json<ApiResponse<'a, &'a str>>, ApiError<'a>> {
-    pool.delete_lang(key, &lang)?;
+    if pool.purge(key)? == 0 {
+        return Err(Error::Status(Status::NotFound.code, Status::NotFound.to_string()).into());
+    }
     Ok(Json(ApiResponse::ok("ok")))
 }
@@ -1,10 +1,14 @@
 use super::controller::Controller;
-use crate::{auth::claims::AdminClaims, repositories::user_settings::repo::UserSettingsRepo};
+use crate::{
+    auth::claims::AdminClaims,
+    models::{user_name::parse_scripts, user_settings::RegistrationSettings},
+    repositories::user_settings::repo::UserSettingsRepo,
+};
 use petompp_web_models::{
-    error::ApiError,
+    error::{ApiError, Error},
     models::{api_response::ApiResponse, user_settings_dto::UserSettingsDto},
 };
-use rocket::{get, post, routes, serde::json::Json};
+use rocket::{get, http::Status, post, routes, serde::json::Json};
 
 pub struct UserSettingsController;
 
@@ -14,7 +18,7 @@ impl Controller for UserSettingsController {
     }
 
     fn routes(&self) -> Vec<rocket::Route> {
-        routes![get, update]
+        routes![get, update, get_registration, update_registration]
     }
 }
 
@@ -33,3 +37,34 @@ async fn update(
     let settings = pool.update(&settings.into_inner().into())?;
     Ok(Json(ApiResponse::ok(settings.into())))
 }
+
+#[get("/registration")]
+async fn get_registration(
+    _claims: AdminClaims,
+    pool: &dyn UserSettingsRepo,
+) -> Result<Json<ApiResponse<RegistrationSettings>>, ApiError> {
+    let settings = pool.get()?;
+    Ok(Json(ApiResponse::ok(settings.into())))
+}
+
+#[post("/registration", data = "<settings>")]
+async fn update_registration(
+    _claims: AdminClaims,
+    settings: Json<RegistrationSettings>,
+    pool: &dyn UserSettingsRepo,
+) -> Result<Json<ApiResponse<RegistrationSettings>>, ApiError> {
+    if settings.registration_expiry_days.is_some_and(|d| d <= 0)
+        || settings.deleted_user_retention_days.is_some_and(|d| d < 0)
+    {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Registration expiry must be positive and retention must not be negative".to_string(),
+        )
+        .into());
+    }
+    if let Some(scripts) = &settings.allowed_scripts {
+        parse_scripts(scripts).map_err(|e| Error::Status(Status::BadRequest.code, e))?;
+    }
+    let settings = pool.update_registration(&settings)?;
+    Ok(Json(ApiResponse::ok(settings.into())))
+}
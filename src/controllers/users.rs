@@ -5,9 +5,28 @@ use crate::{
         token::create_token,
     },
     controllers::controller::Controller,
-    models::{role::Role, user::User},
-    repositories::{query_config::QueryConfig, user::repo::UserRepo},
+    models::{
+        client_info::ClientInfo,
+        login::{Login, LoginMethod},
+        registration::{Registration, RegistrationMode},
+        role::Role,
+        user::{Suspension, User, UserDetails},
+        user_bulk::{BulkUserRequest, BulkUserResult},
+        user_export::{ExportFormat, ExportedBlob, PasswordConfirmation, UserExport},
+        user_name::UserName,
+        user_preferences::{UserPreferences, UserPreferencesPatch},
+    },
+    repositories::{
+        login::repo::LoginRepo,
+        query_config::QueryConfig,
+        registration::repo::RegistrationRepo,
+        reserved_name::repo::ReservedNameRepo,
+        user::repo::{BulkSelection, UserRepo},
+        user_preferences::repo::UserPreferencesRepo,
+    },
+    services::{azure_blob::AzureBlobService, user_export::create_archive},
 };
+use azure_storage_blobs::prelude::ClientBuilder;
 use petompp_web_models::{
     error::{ApiError, AuthError, Error, RegisterError, UserError},
     models::{
@@ -16,8 +35,15 @@ use petompp_web_models::{
         user_settings_dto::UserSettingsDto, username_requirements::UsernameRequirements,
     },
 };
-use rocket::{delete, get, http::Status, post, routes, serde::json::Json, State};
+use rocket::{
+    delete, get,
+    http::{ContentType, Header, Status},
+    patch, post, routes,
+    serde::json::Json,
+    Responder, State,
+};
 use serde::{Deserialize, Serialize};
+use unicode_script::Script;
 
 pub struct UsersController;
 
@@ -27,24 +53,63 @@ impl Controller for UsersController {
     }
 
     fn routes(&self) -> Vec<rocket::Route> {
-        routes![create, login, get_self, activate, get_all, delete]
+        routes![
+            create,
+            login,
+            get_self,
+            rename,
+            get_preferences,
+            update_preferences,
+            get_own_logins,
+            get_logins,
+            export,
+            delete_self,
+            activate,
+            get_all,
+            delete,
+            bulk,
+            suspend,
+            unsuspend
+        ]
     }
 }
 
-#[post("/", data = "<credentials>")]
+#[post("/?<invite>", data = "<credentials>")]
 fn create<'a>(
     credentials: Json<Credentials>,
+    invite: Option<String>,
+    client: ClientInfo,
     pool: &'a dyn UserRepo,
     settings_pool: &'a dyn UserSettingsRepo,
+    reserved_pool: &'a dyn ReservedNameRepo,
 ) -> Result<Json<ApiResponse<'a, UserData>>, ApiError<'a>> {
-    let dto: UserSettingsDto = settings_pool.get()?.into();
+    let settings = settings_pool.get()?;
+    match (settings.registration_mode(), &invite) {
+        (RegistrationMode::Closed, _) => {
+            return Err(
+                Error::Status(Status::Forbidden.code, "Registration is closed".to_string()).into(),
+            )
+        }
+        (RegistrationMode::InviteOnly, None) => {
+            return Err(Error::Status(
+                Status::Forbidden.code,
+                "Registration requires an invitation code".to_string(),
+            )
+            .into())
+        }
+        _ => {}
+    }
+    let allowed_scripts = settings.allowed_scripts();
+    let dto: UserSettingsDto = settings.into();
     let (username_req, password_req): (UsernameRequirements, PasswordRequirements) = dto
         .try_into()
         .map_err(|_| Error::Status(500, Status::InternalServerError.to_string()))?;
-    let username_errors = match username_req.validate(&credentials.name.as_str()) {
-        Ok(_) => Vec::new(),
-        Err(e) => e.into_iter().map(|e| e.to_string()).collect::<Vec<_>>(),
-    };
+    let username_errors = validate_name(
+        &credentials.name,
+        &username_req,
+        allowed_scripts.as_deref(),
+        reserved_pool,
+    )?;
     let password_errors = match password_req.validate(&credentials.password.as_str()) {
         Ok(_) => Vec::new(),
         Err(e) => e.into_iter().map(|e| e.to_string()).collect::<Vec<_>>(),
@@ -61,7 +126,80 @@ fn create<'a>(
         credentials.password.clone(),
         Role::User,
     );
-    let user = pool.create(&user)?;
+    let registration = Registration {
+        invitation_code: invite,
+        ..Registration::from(&client)
+    };
+    let user = pool.create(&user, &registration)?;
+    Ok(Json(ApiResponse::ok(user.into())))
+}
+
+/// Validates a new user name against the settings and the reserved names.
+fn validate_name(
+    name: &str,
+    username_req: &UsernameRequirements,
+    allowed_scripts: Option<&[Script]>,
+    reserved_pool: &dyn ReservedNameRepo,
+) -> Result<Vec<String>, Error> {
+    let mut errors = match username_req.validate(&name) {
+        Ok(_) => Vec::new(),
+        Err(e) => e.into_iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+    };
+    if let Some(allowed) = allowed_scripts {
+        let disallowed = UserName(name.to_string()).disallowed_scripts(allowed);
+        if !disallowed.is_empty() {
+            errors.push(format!(
+                "Name uses scripts that are not allowed: {}",
+                disallowed
+                    .iter()
+                    .map(|s| s.full_name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+    if reserved_pool.find_match(name)?.is_some() {
+        errors.push(format!("Name {} is reserved", name.trim()));
+    }
+    Ok(errors)
+}
+
+#[derive(Deserialize)]
+struct NameChange {
+    name: String,
+}
+
+#[post("/me/name", data = "<change>")]
+async fn rename<'a>(
+    claims: Claims,
+    change: Json<NameChange>,
+    pool: &'a dyn UserRepo,
+    settings_pool: &'a dyn UserSettingsRepo,
+    reserved_pool: &'a dyn ReservedNameRepo,
+) -> Result<Json<ApiResponse<'a, UserDetails>>, ApiError<'a>> {
+    let name = change.name.trim();
+    let settings = settings_pool.get()?;
+    let allowed_scripts = settings.allowed_scripts();
+    let dto: UserSettingsDto = settings.into();
+    let (username_req, _): (UsernameRequirements, PasswordRequirements) = dto
+        .try_into()
+        .map_err(|_| Error::Status(500, Status::InternalServerError.to_string()))?;
+    let username_errors = validate_name(
+        name,
+        &username_req,
+        allowed_scripts.as_deref(),
+        reserved_pool,
+    )?;
+    if !username_errors.is_empty() {
+        return Err(Error::Register(RegisterError {
+            username_errors,
+            password_errors: Vec::new(),
+        })
+        .into());
+    }
+    let user = pool
+        .rename(claims.sub, name)?
+        .ok_or_else(|| Error::User(UserError::NotFound(claims.sub.to_string())))?;
     Ok(Json(ApiResponse::ok(user.into())))
 }
 
@@ -74,16 +212,26 @@ struct LoginResponse {
 #[post("/login", data = "<credentials>")]
 async fn login<'a>(
     credentials: Json<Credentials>,
+    client: ClientInfo,
     pool: &'a dyn UserRepo,
+    login_pool: &'a dyn LoginRepo,
     secrets: &State<crate::Secrets>,
 ) -> Result<Json<ApiResponse<'a, LoginResponse>>, ApiError<'a>> {
-    let user = match pool.get_by_name(credentials.name.to_ascii_lowercase())? {
-        Some(user) if user.password.verify(credentials.password.clone()) => user,
-        _ => return Err(Error::User(UserError::InvalidCredentials).into()),
+    // Names that collided on normalization keep their old lowercase form until they are renamed
+    let user = match pool.get_by_name(UserName::normalize(&credentials.name))? {
+        Some(user) => Some(user),
+        None => pool.get_by_name(credentials.name.trim().to_lowercase())?,
     };
-    if !user.confirmed {
-        return Err(Error::User(UserError::NotConfirmed(credentials.name.to_string())).into());
-    }
+    let mut attempt = Login {
+        user_id: user.as_ref().and_then(|u| u.id),
+        name: Login::attempted_name(&credentials.name),
+        method: LoginMethod::Password,
+        ..Login::from(&client)
+    };
+    let result = authenticate(&credentials, user);
+    attempt.success = result.is_ok();
+    login_pool.record(&attempt)?;
+    let user = result?;
     let token = create_token(secrets, &user).map_err(<AuthError as Into<Error>>::into)?;
     Ok(Json(ApiResponse::ok(LoginResponse {
         token,
@@ -91,14 +239,153 @@ async fn login<'a>(
     })))
 }
 
+fn authenticate(credentials: &Credentials, user: Option<User>) -> Result<User, Error> {
+    let user = match user {
+        Some(user)
+            if user.deleted_at.is_none() && user.password.verify(credentials.password.clone()) =>
+        {
+            user
+        }
+        _ => return Err(Error::User(UserError::InvalidCredentials)),
+    };
+    if !user.confirmed {
+        return Err(Error::User(UserError::NotConfirmed(
+            credentials.name.to_string(),
+        )));
+    }
+    // The end of the suspension comes first, so clients can read it from the message
+    if let Some(until) = user.suspended_until() {
+        let mut message = format!("Suspended until {}", until.format("%Y-%m-%dT%H:%M:%SZ"));
+        if let Some(reason) = user.suspension_reason {
+            message.push_str(&format!(": {}", reason));
+        }
+        return Err(Error::Status(Status::Forbidden.code, message));
+    }
+    Ok(user)
+}
+
 #[get("/")]
 async fn get_self(
     claims: Claims,
     pool: &dyn UserRepo,
+) -> Result<Json<ApiResponse<UserDetails>>, ApiError> {
+    let user = pool
+        .get_by_id(claims.sub)?
+        .ok_or_else(|| Error::User(UserError::NotFound(claims.sub.to_string())))?;
+    Ok(Json(ApiResponse::ok(user.into())))
+}
+
+#[get("/me/preferences")]
+async fn get_preferences(
+    claims: Claims,
+    pool: &dyn UserPreferencesRepo,
+) -> Result<Json<ApiResponse<UserPreferences>>, ApiError> {
+    Ok(Json(ApiResponse::ok(pool.get(claims.sub)?)))
+}
+
+#[patch("/me/preferences", data = "<patch>")]
+async fn update_preferences(
+    claims: Claims,
+    patch: Json<UserPreferencesPatch>,
+    pool: &dyn UserPreferencesRepo,
+) -> Result<Json<ApiResponse<UserPreferences>>, ApiError> {
+    Ok(Json(ApiResponse::ok(pool.update(claims.sub, &patch)?)))
+}
+
+/// Newest logins first, a page of them unless the query asks otherwise.
+#[get("/me/logins?<query..>")]
+async fn get_own_logins(
+    claims: Claims,
+    query: QueryConfig,
+    pool: &dyn LoginRepo,
+) -> Result<Json<ApiResponse<Vec<Login>>>, ApiError> {
+    Ok(Json(ApiResponse::ok(pool.get_by_user(
+        claims.sub,
+        &query.or_latest_by("created_at"),
+    )?)))
+}
+
+#[get("/<id>/logins?<query..>")]
+async fn get_logins(
+    _claims: AdminClaims,
+    id: i32,
+    query: QueryConfig,
+    pool: &dyn LoginRepo,
+) -> Result<Json<ApiResponse<Vec<Login>>>, ApiError> {
+    Ok(Json(ApiResponse::ok(pool.get_by_user(id, &query)?)))
+}
+
+#[derive(Responder)]
+enum ExportResponse {
+    Json(Json<ApiResponse<'static, UserExport>>),
+    Zip(Vec<u8>, ContentType, Header<'static>),
+}
+
+#[get("/me/export?<format>")]
+async fn export<'a>(
+    claims: Claims,
+    format: Option<ExportFormat>,
+    pool: &'a dyn UserRepo,
+    registration_pool: &'a dyn RegistrationRepo,
+    login_pool: &'a dyn LoginRepo,
+    preferences_pool: &'a dyn UserPreferencesRepo,
+    blob_service: &'a State<ClientBuilder>,
+) -> Result<ExportResponse, ApiError<'a>> {
+    let user = pool
+        .get_by_id(claims.sub)?
+        .ok_or_else(|| Error::User(UserError::NotFound(claims.sub.to_string())))?;
+    let blobs = blob_service.get_uploaded_by(claims.sub).await?;
+    let export = UserExport {
+        user: user.into(),
+        registrations: registration_pool.get_by_user(claims.sub)?,
+        logins: login_pool.get_by_user(claims.sub, &QueryConfig::default())?,
+        preferences: preferences_pool.get(claims.sub)?,
+        blobs: blobs
+            .iter()
+            .map(|(container, name)| ExportedBlob {
+                container: container.clone(),
+                name: name.clone(),
+            })
+            .collect(),
+        exported_at: chrono::Utc::now().naive_utc(),
+    };
+    match format.unwrap_or_default() {
+        ExportFormat::Json => Ok(ExportResponse::Json(Json(ApiResponse::ok(export)))),
+        ExportFormat::Zip => {
+            let mut contents = Vec::with_capacity(blobs.len());
+            for (container, name) in blobs {
+                let content = blob_service
+                    .get_content(container.clone(), name.clone())
+                    .await?;
+                contents.push((format!("{}/{}", container, name), content));
+            }
+            Ok(ExportResponse::Zip(
+                create_archive(&export, contents)?,
+                ContentType::ZIP,
+                Header::new(
+                    "Content-Disposition",
+                    format!("attachment; filename=\"user-{}.zip\"", claims.sub),
+                ),
+            ))
+        }
+    }
+}
+
+#[delete("/", data = "<confirmation>")]
+async fn delete_self(
+    claims: Claims,
+    confirmation: Json<PasswordConfirmation>,
+    pool: &dyn UserRepo,
 ) -> Result<Json<ApiResponse<UserData>>, ApiError> {
     let user = pool
         .get_by_id(claims.sub)?
         .ok_or_else(|| Error::User(UserError::NotFound(claims.sub.to_string())))?;
+    if !user.password.verify(confirmation.password.clone()) {
+        return Err(Error::User(UserError::InvalidCredentials).into());
+    }
+    let user = pool
+        .delete(claims.sub)?
+        .ok_or_else(|| Error::User(UserError::NotFound(claims.sub.to_string())))?;
     Ok(Json(ApiResponse::ok(user.into())))
 }
 
@@ -107,7 +394,7 @@ fn get_all(
     _claims: AdminClaims,
     query: QueryConfig,
     pool: &dyn UserRepo,
-) -> Result<Json<ApiResponse<Vec<Vec<UserData>>>>, ApiError> {
+) -> Result<Json<ApiResponse<Vec<Vec<UserDetails>>>>, ApiError> {
     let users = pool
         .get_all(&query)?
         .into_iter()
@@ -139,3 +426,69 @@ async fn delete(
         .ok_or_else(|| Error::User(UserError::NotFound(id.to_string())))?;
     Ok(Json(ApiResponse::ok(user.into())))
 }
+
+#[post("/bulk?<dry_run>&<query..>", data = "<request>")]
+async fn bulk(
+    _claims: AdminClaims,
+    dry_run: bool,
+    query: QueryConfig,
+    request: Json<BulkUserRequest>,
+    pool: &dyn UserRepo,
+) -> Result<Json<ApiResponse<Vec<BulkUserResult>>>, ApiError> {
+    let BulkUserRequest { action, ids } = request.into_inner();
+    // Selecting by query requires an explicit range, so an empty request never hits every user
+    let selection = match (ids, &query.range) {
+        (Some(ids), None) => BulkSelection::Ids(ids),
+        (None, Some(_)) => BulkSelection::Query(query),
+        _ => {
+            return Err(Error::Status(
+                Status::BadRequest.code,
+                "Specify either a list of ids or a range".to_string(),
+            )
+            .into())
+        }
+    };
+    Ok(Json(ApiResponse::ok(
+        pool.bulk(&selection, action, dry_run)?,
+    )))
+}
+
+#[post("/<id>/suspend", data = "<suspension>")]
+async fn suspend(
+    claims: AdminClaims,
+    id: i32,
+    suspension: Json<Suspension>,
+    pool: &dyn UserRepo,
+) -> Result<Json<ApiResponse<UserDetails>>, ApiError> {
+    if id == claims.sub {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Cannot suspend yourself".to_string(),
+        )
+        .into());
+    }
+    let Suspension { until, reason } = suspension.into_inner();
+    if until <= chrono::Utc::now().naive_utc() || reason.trim().is_empty() {
+        return Err(Error::Status(
+            Status::BadRequest.code,
+            "Suspension requires a future end date and a reason".to_string(),
+        )
+        .into());
+    }
+    let user = pool
+        .suspend(id, Some(until), Some(reason))?
+        .ok_or_else(|| Error::User(UserError::NotFound(id.to_string())))?;
+    Ok(Json(ApiResponse::ok(user.into())))
+}
+
+#[delete("/<id>/suspend")]
+async fn unsuspend(
+    _claims: AdminClaims,
+    id: i32,
+    pool: &dyn UserRepo,
+) -> Result<Json<ApiResponse<UserDetails>>, ApiError> {
+    let user = pool
+        .suspend(id, None, None)?
+        .ok_or_else(|| Error::User(UserError::NotFound(id.to_string())))?;
+    Ok(Json(ApiResponse::ok(user.into())))
+}
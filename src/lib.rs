@@ -1,6 +1,10 @@
 use crate::controllers::controller::ControllerRegisterer;
 use crate::controllers::users::UsersController;
 use azure_storage_blobs::prelude::ClientBuilder;
+use controllers::invitations::InvitationsController;
+use controllers::languages::LanguagesController;
+use controllers::registrations::RegistrationsController;
+use controllers::reserved_names::ReservedNamesController;
 use controllers::user_settings::UserSettingsController;
 use controllers::{blob::BlobController, resources::ResourcesController};
 use diesel::{
@@ -10,10 +14,15 @@ use diesel::{
 use models::azure::AzureBlobSecrets;
 use petompp_web_models::{error::Error, models::api_response::ApiResponse};
 use repositories::{
-    resources::repo::ResourcesRepo, user::repo::UserRepo, user_settings::repo::UserSettingsRepo,
+    invitation::repo::InvitationRepo, language::repo::LanguageRepo, login::repo::LoginRepo,
+    registration::repo::RegistrationRepo, reserved_name::repo::ReservedNameRepo,
+    resource_revision::repo::ResourceRevisionRepo, resource_variant::repo::ResourceVariantRepo,
+    resources::repo::ResourcesRepo, user::repo::UserRepo,
+    user_preferences::repo::UserPreferencesRepo, user_settings::repo::UserSettingsRepo,
 };
 use rocket::{catch, http::Status, serde::json::Json, Build, Rocket};
 use rocket::{catchers, Request};
+use services::{maintenance::Maintenance, markdown::MarkdownRenderer};
 use std::env;
 
 pub mod auth;
@@ -54,15 +63,29 @@ pub fn build_rocket(secrets: &Secrets, pg_pool: &'static PgPool) -> Rocket<Build
         .add(ResourcesController)
         .add(BlobController)
         .add(UserSettingsController)
+        .add(RegistrationsController)
+        .add(InvitationsController)
+        .add(ReservedNamesController)
+        .add(LanguagesController)
         .mount("/", rocket_cors::catch_all_options_routes())
         .register("/", catchers![err])
         .attach(cors.clone())
+        .attach(Maintenance)
         .manage(cors)
         .manage(secrets.clone())
         .manage(pg_pool)
         .manage::<&'static dyn UserRepo>(pg_pool)
         .manage::<&'static dyn ResourcesRepo>(pg_pool)
         .manage::<&'static dyn UserSettingsRepo>(pg_pool)
+        .manage::<&'static dyn RegistrationRepo>(pg_pool)
+        .manage::<&'static dyn InvitationRepo>(pg_pool)
+        .manage::<&'static dyn LoginRepo>(pg_pool)
+        .manage::<&'static dyn ReservedNameRepo>(pg_pool)
+        .manage::<&'static dyn UserPreferencesRepo>(pg_pool)
+        .manage::<&'static dyn LanguageRepo>(pg_pool)
+        .manage::<&'static dyn ResourceRevisionRepo>(pg_pool)
+        .manage::<&'static dyn ResourceVariantRepo>(pg_pool)
+        .manage(MarkdownRenderer::default())
         .manage::<ClientBuilder>(AzureBlobSecrets::default().into())
 }
 
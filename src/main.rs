@@ -1,6 +1,8 @@
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use lazy_static::lazy_static;
-use petompp_web_api::{build_rocket, get_connection_pool, PgPool, Secrets};
+use petompp_web_api::{
+    build_rocket, get_connection_pool, repositories::user::repo::UserRepo, PgPool, Secrets,
+};
 
 #[macro_use]
 extern crate rocket;
@@ -18,5 +20,9 @@ fn rocket() -> _ {
         let mut conn = PG_POOL.get().unwrap();
         conn.run_pending_migrations(MIGRATIONS).unwrap();
     }
+    // Skeletons need the Unicode confusables data, so the migration leaves them to be filled in here
+    if let Err(e) = UserRepo::normalize_names(&*PG_POOL) {
+        println!("Cannot normalize user names: {:?}", e);
+    }
     build_rocket(&SECRETS, &PG_POOL)
 }
@@ -0,0 +1,61 @@
+use super::lang::Lang;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+const MAX_RANGES: usize = 16;
+
+/// Language ranges from the `Accept-Language` header, most preferred first.
+#[derive(Debug, Clone, Default)]
+pub struct AcceptLanguage(Vec<String>);
+
+impl AcceptLanguage {
+    pub fn parse(header: &str) -> Self {
+        let mut ranges = header
+            .split(',')
+            .take(MAX_RANGES)
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim().to_lowercase();
+                let q = parts
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .next()
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                (!tag.is_empty() && q > 0.0).then_some((tag, q))
+            })
+            .collect::<Vec<_>>();
+        // Stable, so ranges with equal weight keep their order
+        ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        Self(ranges.into_iter().map(|(tag, _)| tag).collect())
+    }
+
+    /// Returns the most preferred of the supported languages.
+    ///
+    /// A range matches a language with the same code or the primary language of it, so `pl-PL` matches `pl`.
+    pub fn negotiate(&self, supported: &[Lang]) -> Option<Lang> {
+        self.0.iter().find_map(|range| {
+            if range == "*" {
+                return Some(Lang::base());
+            }
+            let primary = range.split('-').next().unwrap_or_default();
+            supported
+                .iter()
+                .find(|l| ***l == *range)
+                .or_else(|| supported.iter().find(|l| ***l == primary))
+                .cloned()
+        })
+    }
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for AcceptLanguage {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        Outcome::Success(
+            request
+                .headers()
+                .get_one("Accept-Language")
+                .map(Self::parse)
+                .unwrap_or_default(),
+        )
+    }
+}
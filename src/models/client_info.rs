@@ -0,0 +1,25 @@
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+const USER_AGENT_MAX_LENGTH: usize = 512;
+
+/// Details about the client sending the request, as far as they can be told.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for ClientInfo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        Outcome::Success(Self {
+            ip: request.client_ip().map(|ip| ip.to_string()),
+            user_agent: request
+                .headers()
+                .get_one("User-Agent")
+                .map(|ua| ua.chars().take(USER_AGENT_MAX_LENGTH).collect()),
+        })
+    }
+}
@@ -0,0 +1,37 @@
+use super::role::Role;
+use crate::schema::invitation_codes;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = invitation_codes)]
+pub struct Invitation {
+    pub code: String,
+    pub max_uses: Option<i32>,
+    #[diesel(deserialize_as = i32)]
+    pub uses: Option<i32>,
+    pub role: Option<Role>,
+    pub auto_confirm: bool,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub created_by: Option<i32>,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+impl Invitation {
+    pub fn generate_code() -> String {
+        let mut rng = urandom::csprng();
+        let code: [u8; 12] = rng.next();
+        code.iter().map(|x| format!("{:02x}", x)).collect()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct InvitationDto {
+    pub code: Option<String>,
+    pub max_uses: Option<i32>,
+    pub role: Option<Role>,
+    #[serde(default)]
+    pub auto_confirm: bool,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+}
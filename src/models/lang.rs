@@ -0,0 +1,145 @@
+use crate::schema::languages;
+use deref_derive::Deref;
+use diesel::{Insertable, Queryable};
+use petompp_web_models::models::country::Country;
+use rocket::{
+    async_trait,
+    form::{self, DataField, FromFormField, ValueField},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Code of the language every resource has a value in.
+pub const BASE_LANG: &str = "en";
+
+const LANG_CODE_MAX_LENGTH: usize = 16;
+
+/// Language code of a resource value, like `en`, `pl` or `pt-br`.
+///
+/// Languages known to `Country` are accepted and serialized as before, so existing clients keep working.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deref, Serialize, Deserialize)]
+#[serde(try_from = "ServedLang", into = "ServedLang")]
+pub struct Lang(String);
+
+impl Lang {
+    pub fn new(code: &str) -> Option<Self> {
+        let code = code.trim().to_lowercase();
+        let mut parts = code.split('-');
+        let primary = parts.next().unwrap_or_default();
+        let valid = code.len() <= LANG_CODE_MAX_LENGTH
+            && (2..=3).contains(&primary.len())
+            && primary.chars().all(|c| c.is_ascii_lowercase())
+            && parts.all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_alphanumeric()));
+        valid.then_some(Self(code))
+    }
+
+    /// Reads a `Country` name or a language code.
+    fn parse(value: &str) -> Option<Self> {
+        if let Ok(country) = Country::from_value(ValueField::from_value(value)) {
+            return Some(country.into());
+        }
+        Self::new(value)
+    }
+
+    pub fn base() -> Self {
+        Self(BASE_LANG.to_string())
+    }
+
+    pub fn is_base(&self) -> bool {
+        self.0 == BASE_LANG
+    }
+
+    pub fn country(&self) -> Option<Country> {
+        match self.0.as_str() {
+            "en" => Some(Country::UnitedKingdom),
+            "pl" => Some(Country::Poland),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Lang {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Country> for Lang {
+    fn from(value: Country) -> Self {
+        match value {
+            Country::UnitedKingdom => Self("en".to_string()),
+            Country::Poland => Self("pl".to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl<'r> FromFormField<'r> for Lang {
+    fn from_value(field: ValueField<'r>) -> form::Result<'r, Self> {
+        Self::parse(field.value).ok_or_else(|| form::Error::validation("invalid_lang").into())
+    }
+
+    async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
+        let value = String::from_data(field).await?;
+        Self::parse(&value).ok_or_else(|| form::Error::validation("invalid_lang").into())
+    }
+}
+
+/// Language as sent to clients, a `Country` when there is one for it and the code otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ServedLang {
+    Country(Country),
+    Code(String),
+}
+
+impl From<Lang> for ServedLang {
+    fn from(value: Lang) -> Self {
+        match value.country() {
+            Some(country) => ServedLang::Country(country),
+            None => ServedLang::Code(value.0),
+        }
+    }
+}
+
+impl TryFrom<ServedLang> for Lang {
+    type Error = String;
+
+    fn try_from(value: ServedLang) -> Result<Self, Self::Error> {
+        match value {
+            ServedLang::Country(country) => Ok(country.into()),
+            ServedLang::Code(code) => {
+                Self::new(&code).ok_or_else(|| format!("Invalid language code: {}", code))
+            }
+        }
+    }
+}
+
+#[derive(Default, Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = languages)]
+pub struct Language {
+    pub code: String,
+    pub name: String,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+    /// Language served when a value is missing, the base language is used after the chain ends.
+    pub fallback: Option<String>,
+    /// Postgres text search configuration used to index values, like `english` or `simple`.
+    #[diesel(deserialize_as = String)]
+    pub search_config: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LanguageDto {
+    pub code: String,
+    pub name: String,
+    pub fallback: Option<String>,
+    pub search_config: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LanguageUpdate {
+    pub name: String,
+    pub fallback: Option<String>,
+    pub search_config: Option<String>,
+}
@@ -0,0 +1,88 @@
+use super::client_info::ClientInfo;
+use crate::schema::user_logins;
+use diesel::{
+    deserialize::FromSql, pg::Pg, serialize::ToSql, sql_types::Integer, AsExpression, FromSqlRow,
+    Insertable, Queryable,
+};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+const NAME_MAX_LENGTH: usize = 255;
+
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    AsExpression,
+    FromPrimitive,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginMethod {
+    #[default]
+    Password,
+    ApiKey,
+    Refresh,
+}
+
+impl ToSql<Integer, Pg> for LoginMethod {
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, Pg>,
+    ) -> diesel::serialize::Result {
+        out.write_all(&(*self as i32).to_ne_bytes())?;
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Integer, Pg> for LoginMethod {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        FromPrimitive::from_i32(i32::from_sql(bytes)?).ok_or(Box::new(
+            diesel::result::Error::DeserializationError("Invalid login method".into()),
+        ))
+    }
+}
+
+/// A single login attempt, successful or not.
+///
+/// `user_id` is empty when the attempted name does not belong to any user.
+#[derive(Default, Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = user_logins)]
+pub struct Login {
+    #[diesel(deserialize_as = i32)]
+    pub id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub method: LoginMethod,
+    pub success: bool,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+impl Login {
+    /// Name of the attempt as given, cut to fit the column.
+    pub fn attempted_name(name: &str) -> String {
+        name.chars().take(NAME_MAX_LENGTH).collect()
+    }
+}
+
+impl From<&ClientInfo> for Login {
+    fn from(client: &ClientInfo) -> Self {
+        Self {
+            ip: client.ip.clone(),
+            user_agent: client.user_agent.clone(),
+            ..Default::default()
+        }
+    }
+}
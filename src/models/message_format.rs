@@ -0,0 +1,645 @@
+use super::lang::Lang;
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fmt::Display};
+use unic_langid::LanguageIdentifier;
+
+const PLURAL_KEYWORDS: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+const SIMPLE_TYPES: [&str; 3] = ["number", "date", "time"];
+/// Deepest nesting of `plural` and `select` cases a message may have.
+const MAX_NESTING_DEPTH: usize = 16;
+
+/// Value passed for a placeholder of a message.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MessageArg {
+    Number(f64),
+    Text(String),
+}
+
+impl MessageArg {
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            Self::Text(t) => t.trim().parse().ok(),
+        }
+    }
+}
+
+impl Display for MessageArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(n) => f.write_str(&format_number(*n)),
+            Self::Text(t) => f.write_str(t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageError {
+    pub position: Option<usize>,
+    pub reason: String,
+}
+
+impl MessageError {
+    fn at(position: usize, reason: impl Into<String>) -> Self {
+        Self {
+            position: Some(position),
+            reason: reason.into(),
+        }
+    }
+
+    fn format(reason: impl Into<String>) -> Self {
+        Self {
+            position: None,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for MessageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} at character {}", self.reason, position),
+            None => f.write_str(&self.reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Arg(String),
+    /// `#` inside of a plural case, the number being pluralized
+    Number,
+    Plural {
+        name: String,
+        ordinal: bool,
+        offset: f64,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+    Select {
+        name: String,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+}
+
+/// Resource value in the ICU MessageFormat syntax.
+///
+/// Supports simple, `number`, `date` and `time` placeholders (formatted as given),
+/// `plural` and `selectordinal` with `offset:` and `=N` cases, `select` and apostrophe quoting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message(Vec<Part>);
+
+impl Message {
+    pub fn parse(source: &str) -> Result<Self, MessageError> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        Ok(Self(parser.message(false, false)?))
+    }
+
+    /// Formats the message with the plural rules of `lang`.
+    pub fn format(
+        &self,
+        lang: &Lang,
+        args: &BTreeMap<String, MessageArg>,
+    ) -> Result<String, MessageError> {
+        let formatter = Formatter {
+            cardinal: plural_rules(lang, PluralRuleType::CARDINAL),
+            ordinal: plural_rules(lang, PluralRuleType::ORDINAL),
+            args,
+        };
+        let mut output = String::new();
+        formatter.write(&self.0, None, &mut output)?;
+        Ok(output)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Number of cases the parser is currently inside of
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), MessageError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(MessageError::at(
+                self.pos,
+                format!("Expected '{}'", expected),
+            )),
+        }
+    }
+
+    fn word(&mut self) -> Result<String, MessageError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '=' || c == '.')
+        {
+            self.pos += 1;
+        }
+        match self.pos > start {
+            true => Ok(self.chars[start..self.pos].iter().collect()),
+            false => Err(MessageError::at(start, "Expected a name")),
+        }
+    }
+
+    /// Parses text up to the end of input, or up to the closing brace when `nested`.
+    fn message(&mut self, in_plural: bool, nested: bool) -> Result<Vec<Part>, MessageError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None if nested => {
+                    return Err(MessageError::at(self.pos, "Unclosed '{'"));
+                }
+                None => break,
+                Some('}') if nested => break,
+                Some('}') => return Err(MessageError::at(self.pos, "Unmatched '}'")),
+                Some('{') => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(self.argument(in_plural)?);
+                }
+                Some('#') if in_plural => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Number);
+                    self.pos += 1;
+                }
+                Some('\'') => self.quoted(in_plural, &mut text),
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// `''` is an apostrophe, an apostrophe before a syntax character starts a literal up to the next one.
+    fn quoted(&mut self, in_plural: bool, text: &mut String) {
+        self.pos += 1;
+        match self.peek() {
+            Some('\'') => {
+                text.push('\'');
+                self.pos += 1;
+            }
+            Some(c) if c == '{' || c == '}' || c == '|' || (c == '#' && in_plural) => {
+                while let Some(c) = self.peek() {
+                    self.pos += 1;
+                    if c != '\'' {
+                        text.push(c);
+                    } else if self.peek() == Some('\'') {
+                        text.push('\'');
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            _ => text.push('\''),
+        }
+    }
+
+    fn argument(&mut self, in_plural: bool) -> Result<Part, MessageError> {
+        let start = self.pos;
+        self.pos += 1;
+        self.skip_whitespace();
+        let name = self.word()?;
+        self.skip_whitespace();
+        match self.peek() {
+            Some('}') => {
+                self.pos += 1;
+                return Ok(Part::Arg(name));
+            }
+            Some(',') => self.pos += 1,
+            _ => return Err(MessageError::at(self.pos, "Expected ',' or '}'")),
+        }
+        self.skip_whitespace();
+        let kind_start = self.pos;
+        let kind = self.word()?;
+        self.skip_whitespace();
+        match kind.as_str() {
+            kind if SIMPLE_TYPES.contains(&kind) => {
+                // Styles are accepted but values are formatted as given
+                if self.peek() == Some(',') {
+                    while self.peek().is_some_and(|c| c != '}' && c != '{') {
+                        self.pos += 1;
+                    }
+                }
+                self.expect('}')
+                    .map_err(|_| MessageError::at(start, "Unclosed '{'"))?;
+                Ok(Part::Arg(name))
+            }
+            "plural" | "selectordinal" => {
+                self.expect(',')?;
+                self.skip_whitespace();
+                let mut offset = 0.0;
+                if self.chars[self.pos..].starts_with(&['o', 'f', 'f', 's', 'e', 't', ':']) {
+                    self.pos += 7;
+                    self.skip_whitespace();
+                    let offset_start = self.pos;
+                    offset = self
+                        .word()?
+                        .parse()
+                        .map_err(|_| MessageError::at(offset_start, "Invalid offset"))?;
+                }
+                let cases = self.cases(true)?;
+                if let Some((selector, _)) = cases.iter().find(|(s, _)| {
+                    !PLURAL_KEYWORDS.contains(&s.as_str())
+                        && s.strip_prefix('=')
+                            .is_none_or(|n| n.parse::<f64>().is_err())
+                }) {
+                    return Err(MessageError::at(
+                        start,
+                        format!("Invalid plural case '{}'", selector),
+                    ));
+                }
+                Ok(Part::Plural {
+                    name,
+                    ordinal: kind == "selectordinal",
+                    offset,
+                    cases,
+                })
+            }
+            "select" => {
+                self.expect(',')?;
+                Ok(Part::Select {
+                    name,
+                    cases: self.cases(in_plural)?,
+                })
+            }
+            _ => Err(MessageError::at(
+                kind_start,
+                format!("Unknown argument type '{}'", kind),
+            )),
+        }
+    }
+
+    /// Parses `selector {message}` pairs up to the closing brace of the argument.
+    fn cases(&mut self, in_plural: bool) -> Result<Vec<(String, Vec<Part>)>, MessageError> {
+        let start = self.pos;
+        let mut cases = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                None => return Err(MessageError::at(self.pos, "Unclosed '{'")),
+                _ => {}
+            }
+            let selector = self.word()?;
+            self.skip_whitespace();
+            self.expect('{')?;
+            if self.depth == MAX_NESTING_DEPTH {
+                return Err(MessageError::at(self.pos, "Cases are nested too deeply"));
+            }
+            self.depth += 1;
+            let message = self.message(in_plural, true)?;
+            self.depth -= 1;
+            self.expect('}')?;
+            cases.push((selector, message));
+        }
+        if !cases.iter().any(|(s, _)| s == "other") {
+            return Err(MessageError::at(start, "Missing the 'other' case"));
+        }
+        Ok(cases)
+    }
+}
+
+struct Formatter<'a> {
+    cardinal: Option<PluralRules>,
+    ordinal: Option<PluralRules>,
+    args: &'a BTreeMap<String, MessageArg>,
+}
+
+impl<'a> Formatter<'a> {
+    fn arg(&self, name: &str) -> Result<&'a MessageArg, MessageError> {
+        self.args
+            .get(name)
+            .ok_or_else(|| MessageError::format(format!("Missing argument '{}'", name)))
+    }
+
+    fn write(
+        &self,
+        parts: &[Part],
+        number: Option<f64>,
+        output: &mut String,
+    ) -> Result<(), MessageError> {
+        for part in parts {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Arg(name) => output.push_str(&self.arg(name)?.to_string()),
+                Part::Number => output.push_str(&format_number(number.unwrap_or_default())),
+                Part::Plural {
+                    name,
+                    ordinal,
+                    offset,
+                    cases,
+                } => {
+                    let value = self.arg(name)?.as_number().ok_or_else(|| {
+                        MessageError::format(format!("Argument '{}' must be a number", name))
+                    })?;
+                    let rules = match ordinal {
+                        true => &self.ordinal,
+                        false => &self.cardinal,
+                    };
+                    // Exact cases match the value before the offset is subtracted
+                    let keyword = rules
+                        .as_ref()
+                        .and_then(|r| r.select(value - offset).ok())
+                        .map_or("other", category_keyword);
+                    let case = cases
+                        .iter()
+                        .find(|(s, _)| {
+                            s.strip_prefix('=')
+                                .and_then(|n| n.parse::<f64>().ok())
+                                .is_some_and(|n| n == value)
+                        })
+                        .or_else(|| cases.iter().find(|(s, _)| s == keyword))
+                        .or_else(|| cases.iter().find(|(s, _)| s == "other"));
+                    if let Some((_, case)) = case {
+                        self.write(case, Some(value - offset), output)?;
+                    }
+                }
+                Part::Select { name, cases } => {
+                    let value = self.arg(name)?.to_string();
+                    let case = cases
+                        .iter()
+                        .find(|(s, _)| *s == value)
+                        .or_else(|| cases.iter().find(|(s, _)| s == "other"));
+                    if let Some((_, case)) = case {
+                        self.write(case, number, output)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rules of the language, or of its primary language when there are none for the region.
+fn plural_rules(lang: &Lang, rule_type: PluralRuleType) -> Option<PluralRules> {
+    let primary = lang.split('-').next().unwrap_or_default();
+    [lang.as_str(), primary].into_iter().find_map(|code| {
+        let id = code.parse::<LanguageIdentifier>().ok()?;
+        PluralRules::create(id, rule_type).ok()
+    })
+}
+
+fn category_keyword(category: PluralCategory) -> &'static str {
+    match category {
+        PluralCategory::ZERO => "zero",
+        PluralCategory::ONE => "one",
+        PluralCategory::TWO => "two",
+        PluralCategory::FEW => "few",
+        PluralCategory::MANY => "many",
+        PluralCategory::OTHER => "other",
+    }
+}
+
+fn format_number(number: f64) -> String {
+    match number.fract() == 0.0 && number.abs() < 1e15 {
+        true => (number as i64).to_string(),
+        false => number.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(
+        source: &str,
+        lang: &str,
+        args: &[(&str, MessageArg)],
+    ) -> Result<String, MessageError> {
+        let args = args
+            .iter()
+            .map(|(name, arg)| (name.to_string(), arg.clone()))
+            .collect();
+        Message::parse(source)?.format(&Lang::new(lang).unwrap(), &args)
+    }
+
+    fn parse_error(source: &str) -> String {
+        Message::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn formats_plain_text_and_arguments() {
+        assert_eq!(format("Hello", "en", &[]).unwrap(), "Hello");
+        assert_eq!(
+            format(
+                "Hello { name }, you are {age, number}",
+                "en",
+                &[
+                    ("name", MessageArg::Text("Ann".into())),
+                    ("age", MessageArg::Number(30.0))
+                ]
+            )
+            .unwrap(),
+            "Hello Ann, you are 30"
+        );
+        assert_eq!(
+            format(
+                "{d, date, short}",
+                "en",
+                &[("d", MessageArg::Text("2024-01-01".into()))]
+            )
+            .unwrap(),
+            "2024-01-01"
+        );
+    }
+
+    #[test]
+    fn unquotes_apostrophes() {
+        assert_eq!(format("It''s", "en", &[]).unwrap(), "It's");
+        assert_eq!(format("It's", "en", &[]).unwrap(), "It's");
+        assert_eq!(
+            format("'{name}' and '}'", "en", &[]).unwrap(),
+            "{name} and }"
+        );
+        assert_eq!(format("'{it''s}'", "en", &[]).unwrap(), "{it's}");
+    }
+
+    #[test]
+    fn selects_plural_cases() {
+        let source = "{n, plural, =0 {none} one {# item} other {# items}}";
+        let count = |n: f64| format(source, "en", &[("n", MessageArg::Number(n))]).unwrap();
+        assert_eq!(count(0.0), "none");
+        assert_eq!(count(1.0), "1 item");
+        assert_eq!(count(5.0), "5 items");
+        assert_eq!(count(1.5), "1.5 items");
+        assert_eq!(
+            format(source, "en", &[("n", MessageArg::Text(" 1 ".into()))]).unwrap(),
+            "1 item"
+        );
+    }
+
+    #[test]
+    fn uses_plural_rules_of_the_language() {
+        let source = "{n, plural, one {# plik} few {# pliki} many {# plików} other {# pliku}}";
+        let count = |n: f64| format(source, "pl", &[("n", MessageArg::Number(n))]).unwrap();
+        assert_eq!(count(1.0), "1 plik");
+        assert_eq!(count(3.0), "3 pliki");
+        assert_eq!(count(5.0), "5 plików");
+        assert_eq!(count(22.0), "22 pliki");
+        assert_eq!(count(1.5), "1.5 pliku");
+    }
+
+    #[test]
+    fn falls_back_to_the_primary_language() {
+        let source = "{n, plural, one {one} other {other}}";
+        assert_eq!(
+            format(source, "en-zz", &[("n", MessageArg::Number(1.0))]).unwrap(),
+            "one"
+        );
+    }
+
+    #[test]
+    fn subtracts_the_plural_offset() {
+        let source = "{n, plural, offset:1 =0 {nobody} =1 {you} one {you and # other} other {you and # others}}";
+        let count = |n: f64| format(source, "en", &[("n", MessageArg::Number(n))]).unwrap();
+        assert_eq!(count(0.0), "nobody");
+        assert_eq!(count(1.0), "you");
+        assert_eq!(count(2.0), "you and 1 other");
+        assert_eq!(count(4.0), "you and 3 others");
+    }
+
+    #[test]
+    fn selects_ordinal_cases() {
+        let source = "{n, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}";
+        let place = |n: f64| format(source, "en", &[("n", MessageArg::Number(n))]).unwrap();
+        assert_eq!(place(1.0), "1st");
+        assert_eq!(place(2.0), "2nd");
+        assert_eq!(place(3.0), "3rd");
+        assert_eq!(place(4.0), "4th");
+        assert_eq!(place(11.0), "11th");
+        assert_eq!(place(22.0), "22nd");
+    }
+
+    #[test]
+    fn selects_cases_and_nests_them() {
+        let source = "{g, select, female {{n, plural, one {She has # cat} other {She has # cats}}} other {{n, plural, one {They have # cat} other {They have # cats}}}}";
+        let owner = |g: &str, n: f64| {
+            format(
+                source,
+                "en",
+                &[
+                    ("g", MessageArg::Text(g.into())),
+                    ("n", MessageArg::Number(n)),
+                ],
+            )
+            .unwrap()
+        };
+        assert_eq!(owner("female", 1.0), "She has 1 cat");
+        assert_eq!(owner("male", 2.0), "They have 2 cats");
+    }
+
+    #[test]
+    fn keeps_hash_literal_outside_of_plural() {
+        assert_eq!(format("#1", "en", &[]).unwrap(), "#1");
+        assert_eq!(
+            format(
+                "{n, plural, other {'#' #}}",
+                "en",
+                &[("n", MessageArg::Number(2.0))]
+            )
+            .unwrap(),
+            "# 2"
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_position() {
+        assert_eq!(parse_error("a } b"), "Unmatched '}' at character 2");
+        assert_eq!(parse_error("a {b"), "Expected ',' or '}' at character 4");
+        assert_eq!(parse_error("{}"), "Expected a name at character 1");
+        assert_eq!(
+            parse_error("{n, list}"),
+            "Unknown argument type 'list' at character 4"
+        );
+        assert_eq!(parse_error("{n, number"), "Unclosed '{' at character 0");
+        assert_eq!(
+            parse_error("{n, plural, one {x}}"),
+            "Missing the 'other' case at character 12"
+        );
+        assert_eq!(
+            parse_error("{n, plural, some {x} other {y}}"),
+            "Invalid plural case 'some' at character 0"
+        );
+        assert_eq!(
+            parse_error("{n, plural, offset:x other {y}}"),
+            "Invalid offset at character 19"
+        );
+        assert_eq!(
+            parse_error("{n, select, other {x"),
+            "Unclosed '{' at character 20"
+        );
+    }
+
+    #[test]
+    fn limits_the_nesting_depth() {
+        let nested = |depth: usize| "{s, select, other {".repeat(depth) + &"}}".repeat(depth);
+        assert!(Message::parse(&nested(MAX_NESTING_DEPTH)).is_ok());
+        assert_eq!(
+            Message::parse(&nested(MAX_NESTING_DEPTH + 1))
+                .unwrap_err()
+                .reason,
+            "Cases are nested too deeply"
+        );
+        assert!(Message::parse(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn reports_format_errors() {
+        assert_eq!(
+            format("{name}", "en", &[]).unwrap_err(),
+            MessageError::format("Missing argument 'name'")
+        );
+        assert_eq!(
+            format(
+                "{n, plural, other {#}}",
+                "en",
+                &[("n", MessageArg::Text("many".into()))]
+            )
+            .unwrap_err(),
+            MessageError::format("Argument 'n' must be a number")
+        );
+    }
+
+    #[test]
+    fn formats_numbers_without_trailing_zeros() {
+        assert_eq!(format_number(3.0), "3");
+        assert_eq!(format_number(-2.0), "-2");
+        assert_eq!(format_number(2.25), "2.25");
+        assert_eq!(format_number(1e20), "100000000000000000000");
+    }
+}
@@ -1,7 +1,24 @@
+pub mod accept_language;
 pub mod azure;
+pub mod client_info;
+pub mod invitation;
+pub mod lang;
+pub mod login;
+pub mod message_format;
 pub mod password;
+pub mod preconditions;
+pub mod registration;
+pub mod reserved_name;
+pub mod resource_coverage;
 pub mod resource_data;
+pub mod resource_draft;
+pub mod resource_revision;
+pub mod resource_transfer;
+pub mod resource_variant;
 pub mod role;
 pub mod user;
+pub mod user_bulk;
+pub mod user_export;
 pub mod user_name;
+pub mod user_preferences;
 pub mod user_settings;
@@ -0,0 +1,80 @@
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+/// Conditional request headers, `If-Match`, `If-None-Match` and `If-Modified-Since`.
+///
+/// Entity tags are `"<version>"`, or `"<version>-<variant>"` for a single language and format,
+/// where the version is derived from the time the resource was last changed.
+#[derive(Debug, Clone, Default)]
+pub struct Preconditions {
+    if_match: Option<Vec<String>>,
+    if_none_match: Option<Vec<String>>,
+    if_modified_since: Option<chrono::NaiveDateTime>,
+}
+
+impl Preconditions {
+    pub fn etag(updated_at: &chrono::NaiveDateTime, variant: Option<&str>) -> String {
+        let version = updated_at.and_utc().timestamp_micros();
+        match variant {
+            Some(variant) => format!("\"{:x}-{}\"", version, variant),
+            None => format!("\"{:x}\"", version),
+        }
+    }
+
+    pub fn last_modified(updated_at: &chrono::NaiveDateTime) -> String {
+        updated_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+    }
+
+    /// Whether a change may be applied, tags are compared by version so a tag of any variant matches.
+    pub fn matches(&self, updated_at: &chrono::NaiveDateTime) -> bool {
+        let Some(tags) = &self.if_match else {
+            return true;
+        };
+        let version = Self::etag(updated_at, None);
+        let version = version.trim_matches('"');
+        tags.iter()
+            .any(|t| t == "*" || t.split('-').next() == Some(version))
+    }
+
+    /// Whether the client already has the current representation.
+    ///
+    /// `If-Modified-Since` is only used when there is no `If-None-Match`.
+    pub fn not_modified(&self, etag: &str, updated_at: &chrono::NaiveDateTime) -> bool {
+        if let Some(tags) = &self.if_none_match {
+            let etag = etag.trim_matches('"');
+            return tags.iter().any(|t| t == "*" || t == etag);
+        }
+        self.if_modified_since
+            .is_some_and(|since| updated_at.and_utc().timestamp() <= since.and_utc().timestamp())
+    }
+
+    fn parse_tags(header: &str) -> Vec<String> {
+        header
+            .split(',')
+            .map(|t| {
+                let t = t.trim();
+                t.strip_prefix("W/")
+                    .unwrap_or(t)
+                    .trim_matches('"')
+                    .to_string()
+            })
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for Preconditions {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        let headers = request.headers();
+        Outcome::Success(Self {
+            if_match: headers.get_one("If-Match").map(Self::parse_tags),
+            if_none_match: headers.get_one("If-None-Match").map(Self::parse_tags),
+            if_modified_since: headers
+                .get_one("If-Modified-Since")
+                .and_then(|d| chrono::DateTime::parse_from_rfc2822(d).ok())
+                .map(|d| d.naive_utc()),
+        })
+    }
+}
@@ -0,0 +1,128 @@
+use super::client_info::ClientInfo;
+use crate::schema::user_registrations;
+use diesel::{
+    deserialize::FromSql, pg::Pg, serialize::ToSql, sql_types::Integer, AsExpression, FromSqlRow,
+    Insertable, Queryable,
+};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use rocket::FromFormField;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    AsExpression,
+    FromPrimitive,
+    FromSqlRow,
+    FromFormField,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+}
+
+impl ToSql<Integer, Pg> for RegistrationStatus {
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, Pg>,
+    ) -> diesel::serialize::Result {
+        out.write_all(&(*self as i32).to_ne_bytes())?;
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Integer, Pg> for RegistrationStatus {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        FromPrimitive::from_i32(i32::from_sql(bytes)?).ok_or(Box::new(
+            diesel::result::Error::DeserializationError("Invalid registration status".into()),
+        ))
+    }
+}
+
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    AsExpression,
+    FromPrimitive,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationMode {
+    #[default]
+    Open,
+    InviteOnly,
+    Closed,
+}
+
+impl ToSql<Integer, Pg> for RegistrationMode {
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, Pg>,
+    ) -> diesel::serialize::Result {
+        out.write_all(&(*self as i32).to_ne_bytes())?;
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Integer, Pg> for RegistrationMode {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        FromPrimitive::from_i32(i32::from_sql(bytes)?).ok_or(Box::new(
+            diesel::result::Error::DeserializationError("Invalid registration mode".into()),
+        ))
+    }
+}
+
+#[derive(Default, Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = user_registrations)]
+pub struct Registration {
+    #[diesel(deserialize_as = i32)]
+    pub id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub status: RegistrationStatus,
+    pub reason: Option<String>,
+    pub reviewed_by: Option<i32>,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+    pub reviewed_at: Option<chrono::NaiveDateTime>,
+    pub invitation_code: Option<String>,
+}
+
+impl From<&ClientInfo> for Registration {
+    fn from(client: &ClientInfo) -> Self {
+        Self {
+            ip: client.ip.clone(),
+            user_agent: client.user_agent.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RegistrationReview {
+    pub reason: Option<String>,
+}
@@ -0,0 +1,93 @@
+use super::user_name::UserName;
+use crate::schema::reserved_names;
+use diesel::{
+    deserialize::FromSql, pg::Pg, serialize::ToSql, sql_types::Integer, AsExpression, FromSqlRow,
+    Insertable, Queryable,
+};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    AsExpression,
+    FromPrimitive,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(rename_all = "snake_case")]
+pub enum ReservedNameKind {
+    #[default]
+    Exact,
+    Prefix,
+    Regex,
+}
+
+impl ToSql<Integer, Pg> for ReservedNameKind {
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, Pg>,
+    ) -> diesel::serialize::Result {
+        out.write_all(&(*self as i32).to_ne_bytes())?;
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Integer, Pg> for ReservedNameKind {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        FromPrimitive::from_i32(i32::from_sql(bytes)?).ok_or(Box::new(
+            diesel::result::Error::DeserializationError("Invalid reserved name kind".into()),
+        ))
+    }
+}
+
+#[derive(Default, Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = reserved_names)]
+pub struct ReservedName {
+    #[diesel(deserialize_as = i32)]
+    pub id: Option<i32>,
+    pub pattern: String,
+    pub kind: ReservedNameKind,
+    pub reason: Option<String>,
+    pub created_by: Option<i32>,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+impl ReservedName {
+    /// Checks whether the name is covered by this entry.
+    ///
+    /// Exact and prefix entries compare skeletons, so look-alike names are caught as well.
+    /// Regex entries are matched against the normalized name.
+    pub fn matches(&self, name: &str) -> bool {
+        match self.kind {
+            ReservedNameKind::Exact => {
+                UserName::skeleton(&self.pattern) == UserName::skeleton(name)
+            }
+            ReservedNameKind::Prefix => {
+                UserName::skeleton(name).starts_with(&UserName::skeleton(&self.pattern))
+            }
+            ReservedNameKind::Regex => Regex::new(&self.pattern)
+                .map(|r| r.is_match(&UserName::normalize(name)))
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReservedNameDto {
+    pub pattern: String,
+    #[serde(default)]
+    pub kind: ReservedNameKind,
+    pub reason: Option<String>,
+}
@@ -0,0 +1,70 @@
+use rocket::FromFormField;
+use serde::Serialize;
+
+/// Why a value needs translating, either there is none or the base value changed after it.
+#[derive(Debug, Clone, Copy, PartialEq, FromFormField, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageStatus {
+    Missing,
+    Outdated,
+}
+
+#[derive(Serialize)]
+pub struct CoverageItem {
+    pub key: String,
+    pub lang: String,
+    pub status: CoverageStatus,
+}
+
+/// Translation state of a language against the keys of the base language.
+#[derive(Serialize)]
+pub struct LanguageCoverage {
+    pub lang: String,
+    pub total: usize,
+    pub translated: usize,
+    pub up_to_date: usize,
+    pub translated_percent: f64,
+    pub up_to_date_percent: f64,
+    pub missing: Vec<String>,
+    pub outdated: Vec<String>,
+}
+
+impl LanguageCoverage {
+    pub fn new(lang: String, total: usize, missing: Vec<String>, outdated: Vec<String>) -> Self {
+        let translated = total - missing.len();
+        let up_to_date = translated - outdated.len();
+        let percent = |count: usize| match total {
+            0 => 100.0,
+            total => (count as f64 * 1000.0 / total as f64).round() / 10.0,
+        };
+        Self {
+            translated_percent: percent(translated),
+            up_to_date_percent: percent(up_to_date),
+            lang,
+            total,
+            translated,
+            up_to_date,
+            missing,
+            outdated,
+        }
+    }
+
+    pub fn items(self) -> impl Iterator<Item = CoverageItem> {
+        let lang = self.lang;
+        let missing = self
+            .missing
+            .into_iter()
+            .map(|k| (k, CoverageStatus::Missing));
+        let outdated = self
+            .outdated
+            .into_iter()
+            .map(|k| (k, CoverageStatus::Outdated));
+        missing
+            .chain(outdated)
+            .map(move |(key, status)| CoverageItem {
+                key,
+                lang: lang.clone(),
+                status,
+            })
+    }
+}
@@ -1,32 +1,215 @@
-use diesel::{Insertable, Queryable};
-use petompp_web_models::models::resource_data::ResourceData;
+use super::lang::Lang;
+use crate::schema::{resource_values, resources};
+use diesel::{
+    deserialize::FromSql,
+    pg::Pg,
+    serialize::ToSql,
+    sql_types::{Float4, Integer, Text, Varchar},
+    AsExpression, FromSqlRow, Insertable, Queryable, QueryableByName,
+};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use petompp_web_models::{
+    error::Error,
+    models::{country::Country, resource_data::ResourceData},
+};
+use rocket::{http::Status, FromFormField};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
 
-#[derive(Default, Queryable, Insertable, Clone)]
-#[diesel(table_name = crate::schema::resources)]
+/// Characters separating the namespace of a key from the rest of it.
+const NAMESPACE_SEPARATORS: [char; 3] = ['-', '.', '/'];
+
+#[derive(Default, Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = resources)]
 pub struct Resource {
-    #[diesel(deserialize_as = String)]
-    pub key: Option<String>,
-    #[diesel(deserialize_as = String)]
-    pub en: Option<String>,
-    pub pl: Option<String>,
-}
-
-impl From<Resource> for ResourceData {
-    fn from(val: Resource) -> Self {
-        ResourceData {
-            key: val.key.unwrap(),
-            en: val.en,
-            pl: val.pl,
+    pub key: String,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub updated_at: Option<chrono::NaiveDateTime>,
+    pub description: Option<String>,
+    pub owner: Option<i32>,
+    pub content_type: ResourceContentType,
+    pub max_length: Option<i32>,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+    pub deleted_by: Option<i32>,
+}
+
+impl Resource {
+    /// Part of the key before the first separator, a key without one is a namespace of its own.
+    pub fn namespace(&self) -> &str {
+        self.key
+            .split(NAMESPACE_SEPARATORS)
+            .next()
+            .unwrap_or(&self.key)
+    }
+}
+
+/// How the values of a resource are written and rendered.
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    AsExpression,
+    FromPrimitive,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceContentType {
+    #[default]
+    Markdown,
+    Plain,
+    Html,
+    /// Plain text in the ICU MessageFormat syntax, values are checked to parse on every write.
+    Message,
+}
+
+impl ToSql<Integer, Pg> for ResourceContentType {
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, Pg>,
+    ) -> diesel::serialize::Result {
+        out.write_all(&(*self as i32).to_ne_bytes())?;
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Integer, Pg> for ResourceContentType {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        FromPrimitive::from_i32(i32::from_sql(bytes)?).ok_or(Box::new(
+            diesel::result::Error::DeserializationError("Invalid content type".into()),
+        ))
+    }
+}
+
+/// Metadata of a resource key, replaced as a whole on update.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceMetaDto {
+    pub description: Option<String>,
+    pub owner: Option<i32>,
+    pub content_type: ResourceContentType,
+    pub max_length: Option<i32>,
+}
+
+impl ResourceMetaDto {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.max_length.is_some_and(|l| l <= 0) {
+            return Err(Error::Status(
+                Status::BadRequest.code,
+                "Max length must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl From<Resource> for ResourceMetaDto {
+    fn from(resource: Resource) -> Self {
+        Self {
+            description: resource.description,
+            owner: resource.owner,
+            content_type: resource.content_type,
+            max_length: resource.max_length,
         }
     }
 }
 
-impl From<ResourceData> for Resource {
-    fn from(data: ResourceData) -> Self {
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenameRequest {
+    pub key: String,
+}
+
+#[derive(Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = resource_values)]
+pub struct ResourceValue {
+    pub key: String,
+    pub lang: String,
+    pub value: String,
+}
+
+impl ResourceValue {
+    pub fn new(key: &str, lang: &Lang, value: String) -> Self {
         Self {
-            key: Some(data.key),
-            en: data.en,
-            pl: data.pl,
+            key: key.to_string(),
+            lang: lang.to_string(),
+            value,
         }
     }
+
+    /// Splits `ResourceData` into values of the languages it has.
+    pub fn from_data(data: ResourceData) -> Vec<Self> {
+        [
+            (Country::UnitedKingdom, data.en),
+            (Country::Poland, data.pl),
+        ]
+        .into_iter()
+        .filter_map(|(country, value)| Some(Self::new(&data.key, &country.into(), value?)))
+        .collect()
+    }
+
+    /// Builds `ResourceData` from the values of a single key, languages without a `Country` are left out.
+    pub fn into_data(key: String, values: Vec<Self>) -> ResourceData {
+        let mut data = ResourceData {
+            key,
+            en: None,
+            pl: None,
+        };
+        for value in values {
+            match Lang::new(&value.lang).and_then(|l| l.country()) {
+                Some(Country::UnitedKingdom) => data.en = Some(value.value),
+                Some(Country::Poland) => data.pl = Some(value.value),
+                None => {}
+            }
+        }
+        data
+    }
+}
+
+/// Value served for a requested language, which may come from a fallback language.
+#[derive(Serialize)]
+pub struct ServedValue {
+    pub lang: Lang,
+    pub value: String,
+    pub fallback: bool,
+    #[serde(skip)]
+    pub content_type: ResourceContentType,
+    /// Id of the time-bounded variant served instead of the published value.
+    #[serde(skip)]
+    pub variant: Option<i32>,
+}
+
+/// Form of served values, Markdown as stored or rendered to sanitized HTML.
+#[derive(Debug, Clone, Copy, Default, PartialEq, FromFormField)]
+pub enum ValueFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+/// Keys to fetch at once, either listed or sharing a prefix.
+pub enum ResourceSelection {
+    Keys(Vec<String>),
+    Prefix(String),
+}
+
+/// Value matching a search, `snippet` is escaped HTML with the matched words wrapped in `<mark>` tags.
+#[derive(QueryableByName, Serialize)]
+pub struct SearchResult {
+    #[diesel(sql_type = Varchar)]
+    pub key: String,
+    #[diesel(sql_type = Varchar)]
+    pub lang: String,
+    #[diesel(sql_type = Text)]
+    pub snippet: String,
+    #[diesel(sql_type = Float4)]
+    pub rank: f32,
 }
@@ -0,0 +1,25 @@
+use super::lang::Lang;
+use crate::schema::resource_drafts;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+/// Unpublished value of a resource language, `publish_at` is set when publishing is scheduled.
+#[derive(Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = resource_drafts)]
+pub struct ResourceDraft {
+    pub key: String,
+    pub lang: String,
+    pub value: String,
+    pub author: Option<i32>,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub updated_at: Option<chrono::NaiveDateTime>,
+}
+
+/// Languages to publish, all drafted ones when empty, and an optional time to publish them at.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct PublishRequest {
+    pub langs: Vec<Lang>,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+}
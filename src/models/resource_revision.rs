@@ -0,0 +1,75 @@
+use crate::schema::resource_revisions;
+use diesel::{Insertable, Queryable};
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+/// Content of a resource value after a change, `value` is empty when the language was removed.
+#[derive(Default, Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = resource_revisions)]
+pub struct ResourceRevision {
+    #[diesel(deserialize_as = i32)]
+    pub id: Option<i32>,
+    pub key: String,
+    pub lang: String,
+    pub value: Option<String>,
+    pub author: Option<i32>,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Serialize)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    pub value: String,
+}
+
+/// Line diff between two revisions, both as separate lines and in the unified format.
+#[derive(Serialize)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub lines: Vec<DiffLine>,
+    pub unified: String,
+}
+
+impl RevisionDiff {
+    pub fn new(from: &ResourceRevision, to: &ResourceRevision) -> Self {
+        let old = from.value.as_deref().unwrap_or_default();
+        let new = to.value.as_deref().unwrap_or_default();
+        let diff = TextDiff::from_lines(old, new);
+        let lines = diff
+            .iter_all_changes()
+            .map(|change| DiffLine {
+                tag: match change.tag() {
+                    ChangeTag::Equal => DiffTag::Equal,
+                    ChangeTag::Insert => DiffTag::Insert,
+                    ChangeTag::Delete => DiffTag::Delete,
+                },
+                value: change.value().to_string(),
+            })
+            .collect();
+        let from_id = from.id.unwrap_or_default();
+        let to_id = to.id.unwrap_or_default();
+        let unified = diff
+            .unified_diff()
+            .header(
+                &format!("{}@{}", from.lang, from_id),
+                &format!("{}@{}", to.lang, to_id),
+            )
+            .to_string();
+        Self {
+            from: from_id,
+            to: to_id,
+            lines,
+            unified,
+        }
+    }
+}
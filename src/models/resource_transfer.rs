@@ -0,0 +1,52 @@
+use rocket::FromFormField;
+use serde::Serialize;
+
+/// Format of resource exports and imports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, FromFormField)]
+pub enum TransferFormat {
+    #[default]
+    Json,
+    Csv,
+    Xliff,
+    Po,
+}
+
+impl TransferFormat {
+    pub fn content_type(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::Json => ("application", "json"),
+            Self::Csv => ("text", "csv"),
+            Self::Xliff => ("application", "xliff+xml"),
+            Self::Po => ("text", "x-gettext-translation"),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Xliff => "xlf",
+            Self::Po => "po",
+        }
+    }
+
+    /// XLIFF and PO files hold a single target language next to the base one.
+    pub fn is_bilingual(&self) -> bool {
+        matches!(self, Self::Xliff | Self::Po)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueRef {
+    pub key: String,
+    pub lang: String,
+}
+
+/// Changes an import makes to the stored values.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportDiff {
+    pub added: Vec<ValueRef>,
+    pub changed: Vec<ValueRef>,
+    pub removed: Vec<ValueRef>,
+    pub applied: bool,
+}
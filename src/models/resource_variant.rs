@@ -0,0 +1,84 @@
+use super::lang::Lang;
+use crate::schema::resource_variants;
+use diesel::{Insertable, Queryable};
+use petompp_web_models::error::Error;
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+
+/// Value served instead of the published one while the time is inside its window.
+///
+/// A missing bound leaves that side of the window open, `valid_until` itself is outside of it.
+#[derive(Default, Queryable, Insertable, Clone, Serialize)]
+#[diesel(table_name = resource_variants)]
+pub struct ResourceVariant {
+    #[diesel(deserialize_as = i32)]
+    pub id: Option<i32>,
+    pub key: String,
+    pub lang: String,
+    pub value: String,
+    pub valid_from: Option<chrono::NaiveDateTime>,
+    pub valid_until: Option<chrono::NaiveDateTime>,
+    pub author: Option<i32>,
+    #[diesel(deserialize_as = chrono::NaiveDateTime)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+impl ResourceVariant {
+    /// Latest start or end of the window that is not after `at`.
+    pub fn passed_boundary(&self, at: chrono::NaiveDateTime) -> Option<chrono::NaiveDateTime> {
+        [self.valid_from, self.valid_until]
+            .into_iter()
+            .flatten()
+            .filter(|b| *b <= at)
+            .max()
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.lang == other.lang
+            && match (self.valid_from, other.valid_until) {
+                (Some(from), Some(until)) => from < until,
+                _ => true,
+            }
+            && match (other.valid_from, self.valid_until) {
+                (Some(from), Some(until)) => from < until,
+                _ => true,
+            }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ResourceVariantDto {
+    pub lang: Lang,
+    pub value: String,
+    pub valid_from: Option<chrono::NaiveDateTime>,
+    pub valid_until: Option<chrono::NaiveDateTime>,
+}
+
+impl ResourceVariantDto {
+    pub fn validate(&self) -> Result<(), Error> {
+        let valid = match (self.valid_from, self.valid_until) {
+            (None, None) => false,
+            (Some(from), Some(until)) => from < until,
+            _ => true,
+        };
+        if !valid {
+            return Err(Error::Status(
+                Status::BadRequest.code,
+                "Specify a window that ends after it starts".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn into_variant(self, key: &str, author: i32) -> ResourceVariant {
+        ResourceVariant {
+            key: key.to_string(),
+            lang: self.lang.to_string(),
+            value: self.value,
+            valid_from: self.valid_from,
+            valid_until: self.valid_until,
+            author: Some(author),
+            ..Default::default()
+        }
+    }
+}
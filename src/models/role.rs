@@ -4,6 +4,7 @@ use diesel::{
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use petompp_web_models::models::user::RoleData;
+use serde::{Deserialize, Serialize};
 use std::{fmt::Display, io::Write};
 use strum_macros::{EnumIter, EnumString};
 
@@ -18,6 +19,8 @@ use strum_macros::{EnumIter, EnumString};
     AsExpression,
     FromPrimitive,
     FromSqlRow,
+    Serialize,
+    Deserialize,
 )]
 #[diesel(sql_type = Integer)]
 pub enum Role {
@@ -2,6 +2,7 @@ use super::{password::Password, role::Role, user_name::UserName};
 use crate::schema::users;
 use diesel::prelude::*;
 use petompp_web_models::models::user::UserData;
+use serde::{Deserialize, Serialize};
 
 #[derive(Default, Queryable, Insertable, Clone)]
 pub struct User {
@@ -15,22 +16,36 @@ pub struct User {
     #[diesel(deserialize_as = chrono::NaiveDateTime)]
     pub created_at: Option<chrono::NaiveDateTime>,
     pub deleted_at: Option<chrono::NaiveDateTime>,
+    pub suspended_until: Option<chrono::NaiveDateTime>,
+    pub suspension_reason: Option<String>,
+    pub last_login_at: Option<chrono::NaiveDateTime>,
+    pub name_skeleton: Option<String>,
+    /// Set when the name collides with another one after normalization, until the user is renamed.
+    pub name_conflict: bool,
 }
 
 impl User {
     pub fn new(name: String, password: String, role: Role) -> Self {
         let name = name.trim();
+        let normalized_name = UserName::normalize(name);
+        let name_skeleton = Some(UserName::skeleton(name));
         let name = UserName(name.to_string());
-        let normalized_name = name.to_lowercase();
         let password = Password::new(password);
         Self {
             name,
             normalized_name,
+            name_skeleton,
             password,
             role,
             ..Default::default()
         }
     }
+
+    /// Returns the end of the suspension, if the user is currently suspended.
+    pub fn suspended_until(&self) -> Option<chrono::NaiveDateTime> {
+        self.suspended_until
+            .filter(|until| *until > chrono::Utc::now().naive_utc())
+    }
 }
 
 impl From<User> for UserData {
@@ -45,3 +60,33 @@ impl From<User> for UserData {
         }
     }
 }
+
+/// `UserData` extended with the fields only this API knows about.
+#[derive(Serialize)]
+pub struct UserDetails {
+    #[serde(flatten)]
+    pub data: UserData,
+    pub suspended_until: Option<chrono::NaiveDateTime>,
+    pub suspension_reason: Option<String>,
+    pub last_login_at: Option<chrono::NaiveDateTime>,
+}
+
+impl From<User> for UserDetails {
+    fn from(val: User) -> Self {
+        let suspended_until = val.suspended_until();
+        let suspension_reason = suspended_until.and(val.suspension_reason.clone());
+        let last_login_at = val.last_login_at;
+        UserDetails {
+            data: val.into(),
+            suspended_until,
+            suspension_reason,
+            last_login_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Suspension {
+    pub until: chrono::NaiveDateTime,
+    pub reason: String,
+}
@@ -0,0 +1,49 @@
+use super::{role::Role, user::User};
+use petompp_web_models::models::user::UserData;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkUserAction {
+    Activate,
+    Delete,
+    Restore,
+    SetRole(Role),
+}
+
+impl BulkUserAction {
+    /// Applies the action to the user in place, returning whether anything changed.
+    pub fn apply(&self, user: &mut User) -> bool {
+        match self {
+            BulkUserAction::Activate if !user.confirmed => user.confirmed = true,
+            BulkUserAction::Delete if user.deleted_at.is_none() => {
+                user.deleted_at = Some(chrono::Utc::now().naive_utc())
+            }
+            BulkUserAction::Restore if user.deleted_at.is_some() => user.deleted_at = None,
+            BulkUserAction::SetRole(role) if user.role != *role => user.role = *role,
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkUserRequest {
+    pub action: BulkUserAction,
+    pub ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkUserStatus {
+    Changed,
+    Unchanged,
+    NotFound,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkUserResult {
+    pub id: i32,
+    pub status: BulkUserStatus,
+    pub user: Option<UserData>,
+}
@@ -0,0 +1,34 @@
+use super::{
+    login::Login, registration::Registration, user::UserDetails, user_preferences::UserPreferences,
+};
+use rocket::FromFormField;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, FromFormField)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Zip,
+}
+
+#[derive(Serialize)]
+pub struct ExportedBlob {
+    pub container: String,
+    pub name: String,
+}
+
+/// Everything stored about a user, as returned by the data export.
+#[derive(Serialize)]
+pub struct UserExport {
+    pub user: UserDetails,
+    pub registrations: Vec<Registration>,
+    pub logins: Vec<Login>,
+    pub preferences: UserPreferences,
+    pub blobs: Vec<ExportedBlob>,
+    pub exported_at: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct PasswordConfirmation {
+    pub password: String,
+}
@@ -4,11 +4,53 @@ use diesel::{
     AsExpression, FromSqlRow,
 };
 use std::io::Write;
+use unicode_normalization::UnicodeNormalization;
+use unicode_script::{Script, UnicodeScript};
 
 #[derive(Debug, Clone, Default, AsExpression, FromSqlRow, Deref, DerefMut)]
 #[diesel(sql_type = Text)]
 pub struct UserName(pub String);
 
+impl UserName {
+    /// Returns the NFKC case folded form of the name, used for lookups and uniqueness.
+    pub fn normalize(name: &str) -> String {
+        let name = name.trim().nfkc().collect::<String>();
+        caseless::default_case_fold_str(&name).nfkc().collect()
+    }
+
+    /// Returns the UTS #39 skeleton of the name, which is equal for names that look alike.
+    pub fn skeleton(name: &str) -> String {
+        unicode_security::skeleton(&Self::normalize(name)).collect()
+    }
+
+    /// Returns the scripts used in the name that are not in `allowed`.
+    ///
+    /// Characters shared between scripts, like digits and punctuation, are always allowed.
+    pub fn disallowed_scripts(&self, allowed: &[Script]) -> Vec<Script> {
+        let mut scripts = Vec::new();
+        for script in self.0.chars().map(|c| c.script()) {
+            if !matches!(script, Script::Common | Script::Inherited)
+                && !allowed.contains(&script)
+                && !scripts.contains(&script)
+            {
+                scripts.push(script);
+            }
+        }
+        scripts
+    }
+}
+
+/// Parses comma separated script names, like `Latin,Cyrillic`.
+pub fn parse_scripts(value: &str) -> Result<Vec<Script>, String> {
+    value
+        .split(',')
+        .map(|name| {
+            let name = name.trim();
+            Script::from_full_name(name).ok_or_else(|| format!("Unknown script: {}", name))
+        })
+        .collect()
+}
+
 impl ToSql<Text, Pg> for UserName {
     fn to_sql<'b>(
         &'b self,
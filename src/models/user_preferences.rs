@@ -0,0 +1,128 @@
+use super::lang::Lang;
+use crate::schema::user_preferences;
+use diesel::{
+    backend::Backend, deserialize::FromSql, pg::Pg, serialize::ToSql, sql_types::Text,
+    AsExpression, FromSqlRow, Insertable, Queryable,
+};
+use rocket::serde::json::serde_json;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use std::{io::Write, str::FromStr};
+use strum_macros::{AsRefStr, EnumString};
+
+/// Keys of the stored preferences, each holding a JSON encoded value of its own type.
+#[derive(Debug, Clone, Copy, PartialEq, AsRefStr, EnumString, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+#[strum(serialize_all = "snake_case")]
+pub enum PreferenceKey {
+    Lang,
+    Theme,
+    Editor,
+}
+
+impl ToSql<Text, Pg> for PreferenceKey {
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, Pg>,
+    ) -> diesel::serialize::Result {
+        out.write_all(self.as_ref().as_bytes())?;
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for PreferenceKey {
+    fn from_sql(bytes: <Pg as Backend>::RawValue<'_>) -> diesel::deserialize::Result<Self> {
+        Ok(Self::from_str(&String::from_sql(bytes)?)?)
+    }
+}
+
+#[derive(Queryable, Insertable, Clone)]
+#[diesel(table_name = user_preferences)]
+pub struct UserPreference {
+    pub user_id: i32,
+    pub key: PreferenceKey,
+    pub value: String,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    Light,
+    Dark,
+    System,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EditorSettings {
+    pub font_size: Option<i32>,
+    pub word_wrap: Option<bool>,
+    pub line_numbers: Option<bool>,
+}
+
+#[derive(Default, Serialize)]
+pub struct UserPreferences {
+    /// Language used for resources when a request does not specify one.
+    pub lang: Option<Lang>,
+    pub theme: Option<Theme>,
+    pub editor: Option<EditorSettings>,
+}
+
+impl From<Vec<UserPreference>> for UserPreferences {
+    fn from(value: Vec<UserPreference>) -> Self {
+        fn parse<T: DeserializeOwned>(value: &str) -> Option<T> {
+            serde_json::from_str(value).ok()
+        }
+        // Values that no longer parse are treated as unset
+        let mut preferences = UserPreferences::default();
+        for preference in value {
+            match preference.key {
+                PreferenceKey::Lang => preferences.lang = parse(&preference.value),
+                PreferenceKey::Theme => preferences.theme = parse(&preference.value),
+                PreferenceKey::Editor => preferences.editor = parse(&preference.value),
+            }
+        }
+        preferences
+    }
+}
+
+/// Partial update of the preferences, a missing field is kept and a `null` one is removed.
+#[derive(Default, Deserialize)]
+pub struct UserPreferencesPatch {
+    #[serde(default, deserialize_with = "present")]
+    pub lang: Option<Option<Lang>>,
+    #[serde(default, deserialize_with = "present")]
+    pub theme: Option<Option<Theme>>,
+    #[serde(default, deserialize_with = "present")]
+    pub editor: Option<Option<EditorSettings>>,
+}
+
+fn present<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Option<T>>, D::Error> {
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl UserPreferencesPatch {
+    /// Returns the changed keys with their encoded values, `None` for removed ones.
+    pub fn changes(&self) -> Result<Vec<(PreferenceKey, Option<String>)>, serde_json::Error> {
+        fn encode<T: Serialize>(
+            key: PreferenceKey,
+            value: &Option<Option<T>>,
+        ) -> Option<Result<(PreferenceKey, Option<String>), serde_json::Error>> {
+            value.as_ref().map(|v| {
+                v.as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()
+                    .map(|v| (key, v))
+            })
+        }
+        [
+            encode(PreferenceKey::Lang, &self.lang),
+            encode(PreferenceKey::Theme, &self.theme),
+            encode(PreferenceKey::Editor, &self.editor),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
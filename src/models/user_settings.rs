@@ -1,5 +1,8 @@
+use super::{registration::RegistrationMode, user_name::parse_scripts};
 use diesel::{query_builder::AsChangeset, Insertable, Queryable};
 use petompp_web_models::models::user_settings_dto::UserSettingsDto;
+use serde::{Deserialize, Serialize};
+use unicode_script::Script;
 
 #[derive(Default, Queryable, Insertable, Clone, AsChangeset)]
 #[diesel(table_name = crate::schema::user_settings)]
@@ -25,6 +28,32 @@ pub struct UserSettings {
     password_check_lowercase: Option<bool>,
     #[diesel(deserialize_as = bool)]
     password_check_special_characters: Option<bool>,
+    registration_expiry_days: Option<i32>,
+    #[diesel(deserialize_as = RegistrationMode)]
+    registration_mode: Option<RegistrationMode>,
+    deleted_user_retention_days: Option<i32>,
+    allowed_scripts: Option<String>,
+}
+
+impl UserSettings {
+    pub fn registration_expiry_days(&self) -> Option<i32> {
+        self.registration_expiry_days
+    }
+
+    pub fn registration_mode(&self) -> RegistrationMode {
+        self.registration_mode.unwrap_or_default()
+    }
+
+    pub fn deleted_user_retention_days(&self) -> Option<i32> {
+        self.deleted_user_retention_days
+    }
+
+    /// Scripts allowed in user names, `None` if any script is allowed.
+    pub fn allowed_scripts(&self) -> Option<Vec<Script>> {
+        self.allowed_scripts
+            .as_deref()
+            .and_then(|s| parse_scripts(s).ok())
+    }
 }
 
 impl From<UserSettingsDto> for UserSettings {
@@ -59,3 +88,28 @@ impl From<UserSettings> for UserSettingsDto {
         }
     }
 }
+
+/// Registration and account lifecycle settings, which are not a part of the shared `UserSettingsDto`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, AsChangeset)]
+#[diesel(table_name = crate::schema::user_settings)]
+#[diesel(treat_none_as_null = true)]
+pub struct RegistrationSettings {
+    /// Days after which pending registrations expire, `None` keeps them forever.
+    pub registration_expiry_days: Option<i32>,
+    pub registration_mode: RegistrationMode,
+    /// Days after which deleted users are purged, `None` keeps them forever.
+    pub deleted_user_retention_days: Option<i32>,
+    /// Comma separated scripts allowed in user names (e.g. `Latin,Cyrillic`), `None` allows any script.
+    pub allowed_scripts: Option<String>,
+}
+
+impl From<UserSettings> for RegistrationSettings {
+    fn from(val: UserSettings) -> Self {
+        RegistrationSettings {
+            registration_expiry_days: val.registration_expiry_days,
+            registration_mode: val.registration_mode.unwrap_or_default(),
+            deleted_user_retention_days: val.deleted_user_retention_days,
+            allowed_scripts: val.allowed_scripts,
+        }
+    }
+}
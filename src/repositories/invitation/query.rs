@@ -0,0 +1,15 @@
+use crate::{impl_query_config, schema::invitation_codes};
+use diesel::pg::Pg;
+
+impl_query_config!(
+    invitation_codes::dsl::invitation_codes,
+    invitation_codes::BoxedQuery<'static, Pg>,
+    InvitationsQuery,
+    invitation_codes::code,
+    [
+        (invitation_codes::code, "code"),
+        (invitation_codes::uses, "uses"),
+        (invitation_codes::expires_at, "expires_at"),
+        (invitation_codes::created_at, "created_at"),
+    ]
+);
@@ -0,0 +1,86 @@
+use super::query::InvitationsQuery;
+use crate::{
+    models::invitation::Invitation, repositories::query_config::QueryConfig,
+    schema::invitation_codes, PgPool,
+};
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, NullableExpressionMethods, OptionalExtension,
+    PgConnection, QueryDsl, QueryResult, RunQueryDsl,
+};
+use petompp_web_models::error::Error;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+pub trait InvitationRepo: Send + Sync {
+    fn get_all(&self, query_config: &QueryConfig) -> Result<Vec<Invitation>, Error>;
+    fn create(&self, invitation: &Invitation) -> Result<Invitation, Error>;
+    fn delete(&self, code: &str) -> Result<Option<Invitation>, Error>;
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for &'r dyn InvitationRepo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        let pool = request
+            .guard::<&rocket::State<&dyn InvitationRepo>>()
+            .await
+            .unwrap();
+        Outcome::Success(*pool.inner())
+    }
+}
+
+impl InvitationRepo for PgPool {
+    fn get_all(&self, query_config: &QueryConfig) -> Result<Vec<Invitation>, Error> {
+        let mut conn = self.get()?;
+        Ok(query_config.get_query()?.load::<Invitation>(&mut conn)?)
+    }
+
+    fn create(&self, invitation: &Invitation) -> Result<Invitation, Error> {
+        let mut conn = self.get()?;
+        diesel::insert_into(invitation_codes::dsl::invitation_codes)
+            .values(invitation)
+            .get_result::<Invitation>(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::UniqueViolation,
+                    _,
+                ) => Error::Status(
+                    Status::Conflict.code,
+                    format!("Invitation code {} already exists", invitation.code),
+                ),
+                e => e.into(),
+            })
+    }
+
+    fn delete(&self, code: &str) -> Result<Option<Invitation>, Error> {
+        let mut conn = self.get()?;
+        Ok(diesel::delete(
+            invitation_codes::dsl::invitation_codes.filter(invitation_codes::code.eq(code)),
+        )
+        .get_result::<Invitation>(&mut conn)
+        .optional()?)
+    }
+}
+
+/// Uses up the invitation code, if it is still valid.
+pub fn redeem(conn: &mut PgConnection, code: &str) -> QueryResult<Option<Invitation>> {
+    diesel::update(
+        invitation_codes::dsl::invitation_codes
+            .filter(invitation_codes::code.eq(code))
+            .filter(
+                invitation_codes::max_uses
+                    .is_null()
+                    .or(invitation_codes::uses
+                        .nullable()
+                        .lt(invitation_codes::max_uses)),
+            )
+            .filter(
+                invitation_codes::expires_at
+                    .is_null()
+                    .or(invitation_codes::expires_at.gt(chrono::Utc::now().naive_utc())),
+            ),
+    )
+    .set(invitation_codes::uses.eq(invitation_codes::uses + 1))
+    .get_result::<Invitation>(conn)
+    .optional()
+}
@@ -0,0 +1,170 @@
+use crate::{
+    models::lang::{Lang, Language},
+    schema::languages,
+    PgPool,
+};
+use diesel::{
+    sql_types::{Bool, Text},
+    Connection, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, QueryResult,
+    RunQueryDsl,
+};
+use petompp_web_models::error::Error;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+use std::collections::HashMap;
+
+pub trait LanguageRepo: Send + Sync {
+    fn get_all(&self) -> Result<Vec<Language>, Error>;
+    fn create(&self, language: &Language) -> Result<Language, Error>;
+    fn update(&self, language: &Language) -> Result<Option<Language>, Error>;
+    fn delete(&self, code: &str) -> Result<Option<Language>, Error>;
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for &'r dyn LanguageRepo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        let pool = request
+            .guard::<&rocket::State<&dyn LanguageRepo>>()
+            .await
+            .unwrap();
+        Outcome::Success(*pool.inner())
+    }
+}
+
+impl LanguageRepo for PgPool {
+    fn get_all(&self) -> Result<Vec<Language>, Error> {
+        let mut conn = self.get()?;
+        Ok(languages::dsl::languages
+            .order(languages::code.asc())
+            .load::<Language>(&mut conn)?)
+    }
+
+    fn create(&self, language: &Language) -> Result<Language, Error> {
+        let mut conn = self.get()?;
+        validate_search_config(&mut conn, language.search_config.as_deref())?;
+        diesel::insert_into(languages::dsl::languages)
+            .values(language)
+            .get_result::<Language>(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::UniqueViolation,
+                    _,
+                ) => Error::Status(
+                    Status::Conflict.code,
+                    format!("Language {} already exists", language.code),
+                ),
+                e => fallback_error(e),
+            })
+    }
+
+    fn update(&self, language: &Language) -> Result<Option<Language>, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let fallbacks = fallbacks(conn)?;
+            let mut next = language.fallback.as_deref();
+            while let Some(code) = next {
+                if code == language.code {
+                    return Err(Error::Status(
+                        Status::BadRequest.code,
+                        format!("Fallback of {} would form a cycle", language.code),
+                    ));
+                }
+                next = fallbacks.get(code).and_then(|f| f.as_deref());
+            }
+            validate_search_config(conn, language.search_config.as_deref())?;
+            if let Some(search_config) = &language.search_config {
+                diesel::update(
+                    languages::dsl::languages.filter(languages::code.eq(&language.code)),
+                )
+                .set(languages::search_config.eq(search_config))
+                .execute(conn)?;
+            }
+            diesel::update(languages::dsl::languages.filter(languages::code.eq(&language.code)))
+                .set((
+                    languages::name.eq(&language.name),
+                    languages::fallback.eq(&language.fallback),
+                ))
+                .get_result::<Language>(conn)
+                .optional()
+                .map_err(fallback_error)
+        })
+    }
+
+    fn delete(&self, code: &str) -> Result<Option<Language>, Error> {
+        let mut conn = self.get()?;
+        diesel::delete(languages::dsl::languages.filter(languages::code.eq(code)))
+            .get_result::<Language>(&mut conn)
+            .optional()
+            .map_err(|e| match e {
+                diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                    _,
+                ) => Error::Status(
+                    Status::Conflict.code,
+                    format!("Language {} still has translations", code),
+                ),
+                e => e.into(),
+            })
+    }
+}
+
+fn fallbacks(conn: &mut PgConnection) -> QueryResult<HashMap<String, Option<String>>> {
+    Ok(languages::dsl::languages
+        .select((languages::code, languages::fallback))
+        .load::<(String, Option<String>)>(conn)?
+        .into_iter()
+        .collect())
+}
+
+/// Returns the languages to look values up in, starting with `lang` and ending with the base language.
+pub fn fallback_chain(conn: &mut PgConnection, lang: &Lang) -> QueryResult<Vec<Lang>> {
+    let fallbacks = fallbacks(conn)?;
+    let mut chain = vec![lang.clone()];
+    let mut next = fallbacks.get(&**lang).cloned().flatten();
+    while let Some(lang) = next.and_then(|code| Lang::new(&code)) {
+        // Cycles are rejected on update, this only guards against broken data
+        if chain.contains(&lang) {
+            break;
+        }
+        next = fallbacks.get(&*lang).cloned().flatten();
+        chain.push(lang);
+    }
+    if !chain.iter().any(|l| l.is_base()) {
+        chain.push(Lang::base());
+    }
+    Ok(chain)
+}
+
+/// Rejects text search configurations Postgres doesn't know, values couldn't be indexed with them.
+fn validate_search_config(conn: &mut PgConnection, name: Option<&str>) -> Result<(), Error> {
+    let Some(name) = name else {
+        return Ok(());
+    };
+    let exists = diesel::select(
+        diesel::dsl::sql::<Bool>("EXISTS (SELECT 1 FROM pg_catalog.pg_ts_config WHERE cfgname = ")
+            .bind::<Text, _>(name)
+            .sql(")"),
+    )
+    .get_result::<bool>(conn)?;
+    match exists {
+        true => Ok(()),
+        false => Err(Error::Status(
+            Status::BadRequest.code,
+            format!("Unknown text search configuration {}", name),
+        )),
+    }
+}
+
+fn fallback_error(e: diesel::result::Error) -> Error {
+    match e {
+        diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        ) => Error::Status(
+            Status::BadRequest.code,
+            "Fallback language is not registered".to_string(),
+        ),
+        e => e.into(),
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{impl_query_config, schema::user_logins};
+use diesel::pg::Pg;
+
+impl_query_config!(
+    user_logins::dsl::user_logins,
+    user_logins::BoxedQuery<'static, Pg>,
+    LoginsQuery,
+    user_logins::id,
+    [
+        (user_logins::id, "id"),
+        (user_logins::ip, "ip"),
+        (user_logins::method, "method"),
+        (user_logins::success, "success"),
+        (user_logins::created_at, "created_at"),
+    ]
+);
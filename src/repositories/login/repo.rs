@@ -0,0 +1,53 @@
+use super::query::LoginsQuery;
+use crate::{
+    models::login::Login,
+    repositories::query_config::QueryConfig,
+    schema::{user_logins, users},
+    PgPool,
+};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
+use petompp_web_models::error::Error;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+pub trait LoginRepo: Send + Sync {
+    fn record(&self, login: &Login) -> Result<Login, Error>;
+    fn get_by_user(&self, user_id: i32, query_config: &QueryConfig) -> Result<Vec<Login>, Error>;
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for &'r dyn LoginRepo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        let pool = request
+            .guard::<&rocket::State<&dyn LoginRepo>>()
+            .await
+            .unwrap();
+        Outcome::Success(*pool.inner())
+    }
+}
+
+impl LoginRepo for PgPool {
+    fn record(&self, login: &Login) -> Result<Login, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let login = diesel::insert_into(user_logins::table)
+                .values(login)
+                .get_result::<Login>(conn)?;
+            if let (true, Some(user_id)) = (login.success, login.user_id) {
+                diesel::update(users::dsl::users.filter(users::id.eq(user_id)))
+                    .set(users::last_login_at.eq(login.created_at))
+                    .execute(conn)?;
+            }
+            Ok(login)
+        })
+    }
+
+    fn get_by_user(&self, user_id: i32, query_config: &QueryConfig) -> Result<Vec<Login>, Error> {
+        let mut conn = self.get()?;
+        Ok(query_config
+            .get_query()?
+            .filter(user_logins::user_id.eq(user_id))
+            .load::<Login>(&mut conn)?)
+    }
+}
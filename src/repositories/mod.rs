@@ -1,4 +1,12 @@
+pub mod invitation;
+pub mod language;
+pub mod login;
 pub mod query_config;
+pub mod registration;
+pub mod reserved_name;
+pub mod resource_revision;
+pub mod resource_variant;
 pub mod resources;
 pub mod user;
+pub mod user_preferences;
 pub mod user_settings;
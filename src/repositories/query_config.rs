@@ -1,20 +1,174 @@
 use deref_derive::{Deref, DerefMut};
+use petompp_web_models::error::{Error, QueryValidationError, ValidationError};
 use rocket::{
     async_trait,
     data::ToByteUnit,
     form::{self, DataField, FromFormField, ValueField},
+    http::Status,
     FromForm,
 };
 use std::fmt::Display;
 
-#[derive(Debug, Clone, FromForm)]
+const MAX_ITEM_COUNT: i64 = 1000;
+const MAX_PAGE_SPAN: i64 = 100;
+
+/// Paging and sorting options shared by all listing endpoints.
+///
+/// - `range` - `all`, a single page (`2`) or a page range of up to 100 pages (`1-3`), defaults to `all`
+/// - `items` - page size, defaults to `20` when paging, at most `1000`, ignored with `range=all`
+/// - `sort` - comma separated columns with optional direction, e.g. `role:desc,created_at`
+/// - `order` - direction for `sort` columns that don't specify one, defaults to `asc`
+#[derive(Debug, Clone, Default, FromForm)]
 pub struct QueryConfig {
-    pub range: PageRange,
+    pub range: Option<PageRange>,
     pub items: Option<ItemCount>,
-    pub sort: Option<String>,
+    pub sort: Option<SortColumns>,
     pub order: Option<SortOrder>,
 }
 
+impl QueryConfig {
+    pub fn sort_columns(&self) -> Result<Vec<(String, SortOrder)>, QueryConfigError> {
+        let Some(sort) = &self.sort else {
+            return match self.order {
+                Some(_) => Err(QueryConfigError::OrderWithoutSort),
+                None => Ok(Vec::new()),
+            };
+        };
+        let mut columns: Vec<(String, SortOrder)> = Vec::with_capacity(sort.len());
+        for column in sort.iter() {
+            if columns.iter().any(|(name, _)| name == &column.name) {
+                return Err(QueryConfigError::DuplicateColumn(column.name.clone()));
+            }
+            let order = column.order.or(self.order).unwrap_or_default();
+            columns.push((column.name.clone(), order));
+        }
+        Ok(columns)
+    }
+
+    /// Sorts by `column` descending and returns the first page, unless the client chose the sort or the page.
+    pub fn or_latest_by(mut self, column: &str) -> Self {
+        if self.sort.is_none() && self.order.is_none() {
+            self.sort = Some(SortColumns(vec![SortColumn {
+                name: column.to_string(),
+                order: Some(SortOrder::Desc),
+            }]));
+        }
+        if self.range.is_none() && self.items.is_none() {
+            self.range = Some(PageRange::Single(0));
+        }
+        self
+    }
+
+    pub fn pagination(&self) -> Result<Pagination, QueryConfigError> {
+        // The whole range ignores the item count, as it always has
+        if let Some(PageRange::All) = self.range {
+            return Ok(Pagination::default());
+        }
+        if let Some(items) = self.items {
+            if *items <= 0 || *items > MAX_ITEM_COUNT {
+                return Err(QueryConfigError::InvalidItemCount(*items));
+            }
+        }
+        let count = *self.items.unwrap_or_default();
+        match (&self.range, &self.items) {
+            (None, None) | (Some(PageRange::All), _) => Ok(Pagination::default()),
+            (None, Some(_)) => Ok(Pagination {
+                limit: Some(count),
+                offset: 0,
+            }),
+            (Some(PageRange::Single(page)), _) if *page < 0 => {
+                Err(QueryConfigError::InvalidPage(*page))
+            }
+            (Some(PageRange::Single(page)), _) => Ok(Pagination {
+                limit: Some(count),
+                offset: count
+                    .checked_mul(*page)
+                    .ok_or(QueryConfigError::InvalidPage(*page))?,
+            }),
+            (Some(PageRange::Range(start, end)), _)
+                if *start < 0 || end < start || end - start >= MAX_PAGE_SPAN =>
+            {
+                Err(QueryConfigError::InvalidRange(*start, *end))
+            }
+            (Some(PageRange::Range(start, end)), _) => Ok(Pagination {
+                limit: Some(count * (end - start + 1)),
+                offset: count
+                    .checked_mul(*start)
+                    .ok_or(QueryConfigError::InvalidRange(*start, *end))?,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pagination {
+    pub limit: Option<i64>,
+    pub offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryConfigError {
+    InvalidColumn(String),
+    DuplicateColumn(String),
+    OrderWithoutSort,
+    InvalidItemCount(i64),
+    InvalidPage(i64),
+    InvalidRange(i64, i64),
+}
+
+impl Display for QueryConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryConfigError::InvalidColumn(column) => write!(f, "Invalid sort column: {}", column),
+            QueryConfigError::DuplicateColumn(column) => {
+                write!(f, "Sort column specified more than once: {}", column)
+            }
+            QueryConfigError::OrderWithoutSort => f.write_str("Sort order requires a sort column"),
+            QueryConfigError::InvalidItemCount(count) => {
+                write!(
+                    f,
+                    "Item count must be between 1 and {}, got {}",
+                    MAX_ITEM_COUNT, count
+                )
+            }
+            QueryConfigError::InvalidPage(page) => {
+                write!(f, "Page must not be negative or too large, got {}", page)
+            }
+            QueryConfigError::InvalidRange(start, end) => {
+                write!(
+                    f,
+                    "Invalid page range: {}-{}, at most {} pages can be requested at once",
+                    start, end, MAX_PAGE_SPAN
+                )
+            }
+        }
+    }
+}
+
+impl From<QueryConfigError> for Error {
+    fn from(value: QueryConfigError) -> Self {
+        match value {
+            QueryConfigError::InvalidColumn(column) => Error::Validation(ValidationError::Query(
+                QueryValidationError::InvalidColumn(column),
+            )),
+            e => Error::Status(Status::BadRequest.code, e.to_string()),
+        }
+    }
+}
+
+async fn read_field_data<'r>(field: DataField<'r, '_>, limit: &str) -> form::Result<'r, String> {
+    // Retrieve the configured data limit or use `256KiB` as default.
+    let limit = field.request.limits().get(limit).unwrap_or(256.kibibytes());
+
+    // Read the capped data stream, returning a limit error as needed.
+    let bytes = field.data.open(limit).into_bytes().await?;
+    if !bytes.is_complete() {
+        Err((None, Some(limit)))?;
+    }
+
+    String::from_utf8(bytes.into_inner()).map_err(|_| form::Error::validation("invalid_str").into())
+}
+
 #[derive(Debug, Clone, Copy, Deref, DerefMut)]
 pub struct ItemCount(i64);
 
@@ -31,35 +185,27 @@ impl<'r> FromFormField<'r> for ItemCount {
     }
 
     async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
-        // Retrieve the configured data limit or use `256KiB` as default.
-        let limit = field
-            .request
-            .limits()
-            .get("sort_order")
-            .unwrap_or(256.kibibytes());
-
-        // Read the capped data stream, returning a limit error as needed.
-        let bytes = field.data.open(limit).into_bytes().await?;
-        if !bytes.is_complete() {
-            Err((None, Some(limit)))?;
-        }
-
-        // Store the bytes in request-local cache.
-        let bytes = bytes.into_inner();
-        let bytes = rocket::request::local_cache!(field.request, bytes);
-        let value =
-            String::from_utf8(bytes.into()).map_err(|_| form::Error::validation("invalid_str"))?;
-
-        Ok(Self(value.parse()?))
+        Ok(Self(read_field_data(field, "item_count").await?.parse()?))
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub enum SortOrder {
+    #[default]
     Asc,
     Desc,
 }
 
+impl SortOrder {
+    fn parse<'r>(value: &str) -> form::Result<'r, Self> {
+        match value {
+            "asc" => Ok(Self::Asc),
+            "desc" => Ok(Self::Desc),
+            _ => Err(form::Error::validation("invalid_sort_order").into()),
+        }
+    }
+}
+
 impl Display for SortOrder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -72,37 +218,54 @@ impl Display for SortOrder {
 #[async_trait]
 impl<'r> FromFormField<'r> for SortOrder {
     fn from_value(field: ValueField<'r>) -> form::Result<'r, Self> {
-        match field.value {
-            "asc" => Ok(Self::Asc),
-            "desc" => Ok(Self::Desc),
-            _ => Err(form::Error::validation("invalid_sort_order").into()),
-        }
+        Self::parse(field.value)
     }
 
     async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
-        // Retrieve the configured data limit or use `256KiB` as default.
-        let limit = field
-            .request
-            .limits()
-            .get("sort_order")
-            .unwrap_or(256.kibibytes());
-
-        // Read the capped data stream, returning a limit error as needed.
-        let bytes = field.data.open(limit).into_bytes().await?;
-        if !bytes.is_complete() {
-            Err((None, Some(limit)))?;
-        }
+        Self::parse(&read_field_data(field, "sort_order").await?)
+    }
+}
 
-        // Store the bytes in request-local cache.
-        let bytes = bytes.into_inner();
-        let bytes = rocket::request::local_cache!(field.request, bytes);
-        let value =
-            String::from_utf8(bytes.into()).map_err(|_| form::Error::validation("invalid_str"))?;
-        match value.as_str() {
-            "asc" => Ok(Self::Asc),
-            "desc" => Ok(Self::Desc),
-            _ => Err(form::Error::validation("invalid_sort_order").into()),
-        }
+#[derive(Debug, Clone)]
+pub struct SortColumn {
+    pub name: String,
+    pub order: Option<SortOrder>,
+}
+
+/// Sort columns in priority order, parsed from `name[:asc|desc][,name[:asc|desc]]...`.
+#[derive(Debug, Clone, Deref)]
+pub struct SortColumns(Vec<SortColumn>);
+
+impl SortColumns {
+    fn parse<'r>(value: &str) -> form::Result<'r, Self> {
+        value
+            .split(',')
+            .map(|column| -> form::Result<'r, SortColumn> {
+                let (name, order) = match column.split_once(':') {
+                    Some((name, order)) => (name.trim(), Some(SortOrder::parse(order.trim())?)),
+                    None => (column.trim(), None),
+                };
+                match name.is_empty() {
+                    true => Err(form::Error::validation("invalid_sort_column").into()),
+                    false => Ok(SortColumn {
+                        name: name.to_string(),
+                        order,
+                    }),
+                }
+            })
+            .collect::<form::Result<'r, Vec<_>>>()
+            .map(Self)
+    }
+}
+
+#[async_trait]
+impl<'r> FromFormField<'r> for SortColumns {
+    fn from_value(field: ValueField<'r>) -> form::Result<'r, Self> {
+        Self::parse(field.value)
+    }
+
+    async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
+        Self::parse(&read_field_data(field, "sort").await?)
     }
 }
 
@@ -113,11 +276,10 @@ pub enum PageRange {
     Range(i64, i64),
 }
 
-#[async_trait]
-impl<'r> FromFormField<'r> for PageRange {
-    fn from_value(field: ValueField<'r>) -> form::Result<'r, Self> {
+impl PageRange {
+    fn parse<'r>(value: &str) -> form::Result<'r, Self> {
         // all, 1, 1-10
-        match field.value {
+        match value {
             "all" => Ok(Self::All),
             value => match value.find('-') {
                 Some(index) => {
@@ -130,164 +292,53 @@ impl<'r> FromFormField<'r> for PageRange {
             },
         }
     }
+}
 
-    async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
-        // Retrieve the configured data limit or use `256KiB` as default.
-        let limit = field
-            .request
-            .limits()
-            .get("page_range")
-            .unwrap_or(256.kibibytes());
-
-        // Read the capped data stream, returning a limit error as needed.
-        let bytes = field.data.open(limit).into_bytes().await?;
-        if !bytes.is_complete() {
-            Err((None, Some(limit)))?;
-        }
+#[async_trait]
+impl<'r> FromFormField<'r> for PageRange {
+    fn from_value(field: ValueField<'r>) -> form::Result<'r, Self> {
+        Self::parse(field.value)
+    }
 
-        // Store the bytes in request-local cache.
-        let bytes = bytes.into_inner();
-        let bytes = rocket::request::local_cache!(field.request, bytes);
-        let value =
-            String::from_utf8(bytes.into()).map_err(|_| form::Error::validation("invalid_str"))?;
-        match value.as_str() {
-            "all" => Ok(Self::All),
-            value => match value.find('-') {
-                Some(index) => {
-                    let (start, end) = value.split_at(index);
-                    let start = start.parse()?;
-                    let end = end[1..].parse()?;
-                    Ok(Self::Range(start, end))
-                }
-                None => Ok(Self::Single(value.parse()?)),
-            },
-        }
+    async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
+        Self::parse(&read_field_data(field, "page_range").await?)
     }
 }
 
+/// Implements a `get_query` trait for `QueryConfig` that builds a boxed, sorted and paged query.
+///
+/// Every sort is followed by the primary key, so results are always deterministic.
 #[macro_export]
 macro_rules! impl_query_config {
-    ($dsl_table:expr, $table:ty, $boxed:ty, $type:ident, [$(($column:expr, $name:expr),)*]) => {
-        use $crate::{
-            repositories::{
-                query_config::{PageRange, QueryConfig, SortOrder},
-            },
-        };
-        use petompp_web_models::error::{Error, ValidationError, QueryValidationError};
-        use diesel::{pg::Pg, query_builder::QueryFragment, AppearsOnTable, ExpressionMethods, QueryDsl};
-
+    ($dsl_table:expr, $boxed:ty, $type:ident, $primary_key:expr, [$(($column:expr, $name:literal),)*]) => {
         pub trait $type {
-            fn get_query(&self) -> Result<$boxed, Error>;
+            fn get_query(&self) -> Result<$boxed, petompp_web_models::error::Error>;
         }
 
-        impl $type for QueryConfig {
-            fn get_query(&self) -> Result<$boxed, Error> {
-                match (
-                    &self.range,
-                    &self.items,
-                    &self.sort,
-                    &self.order,
-                ) {
-                    // Proper All
-                    (PageRange::All, None, None, None)
-                    // All with invalid item count or invalid sort
-                    | (PageRange::All, None, None, Some(_))
-                    | (PageRange::All, None, Some(_), None)
-                    | (PageRange::All, Some(_), None, None)
-                    | (PageRange::All, Some(_), None, Some(_))
-                    | (PageRange::All, Some(_), Some(_), None)
-                    // Single with invalid item count or invalid sort
-                    | (PageRange::Single(_), None, None, None)
-                    | (PageRange::Single(_), None, None, Some(_))
-                    | (PageRange::Single(_), None, Some(_), None)
-                    // Range with invalid item count or invalid sort
-                    | (PageRange::Range(_, _), None, None, None)
-                    | (PageRange::Range(_, _), None, None, Some(_))
-                    | (PageRange::Range(_, _), None, Some(_), None)
-                     => {
-                        Ok($dsl_table.into_boxed())
-                    }
-                    // All with valid sort
-                    (PageRange::All, None, Some(column), Some(order))
-                    // All with invalid item count and valid sort
-                    | (PageRange::All, Some(_), Some(column), Some(order))
-                    // Single with invalid item count and valid sort
-                    | (PageRange::Single(_), None, Some(column), Some(order))
-                    // Range with invalid item count and valid sort
-                    | (PageRange::Range(_, _), None, Some(column), Some(order))
-                     => {
-                        Ok(sort_by_column_str($dsl_table.into_boxed(), column.as_str(), &order)?)
-                    }
-                    // Single with valid item count
-                    (PageRange::Single(i), Some(count), None, None)
-                    // Single with valid item count and invalid sort
-                    | (PageRange::Single(i), Some(count), None, Some(_))
-                    | (PageRange::Single(i), Some(count), Some(_), None)
-                     => {
-                        if *i <= 0 {
-                            return Ok($dsl_table.limit(**count).into_boxed());
-                        }
-                        Ok($dsl_table.limit(**count).offset(**count * *i).into_boxed())
-                    }
-                    // Single with valid item count and valid sort
-                    (PageRange::Single(i), Some(count), Some(column), Some(order)) => {
-                        if *i <= 0 {
-                            return Ok(sort_by_column_str($dsl_table.into_boxed(), column, order)?.limit(**count));
-                        }
-                        Ok(sort_by_column_str($dsl_table.into_boxed(), column, order)?.limit(**count).offset(**count * *i))
-                    }
-                    // Range with valid item count
-                    (PageRange::Range(start, end), Some(count), None, None)
-                    // Range with valid item count and invalid sort
-                    | (PageRange::Range(start, end), Some(count), None, Some(_))
-                    | (PageRange::Range(start, end), Some(count), Some(_), None)
-                     => {
-                        let pages = (end - start).max(0) + 1;
-                        if *start <= 0 {
-                            return Ok($dsl_table.limit(**count * pages).into_boxed());
-                        }
-                        Ok($dsl_table.limit(**count * pages).offset(**count * start).into_boxed())
-                    }
-                    // Range with valid item count and valid sort
-                    (PageRange::Range(start, end), Some(count), Some(column), Some(sort)) => {
-                        let pages = (end - start).max(0) + 1;
-                        if *start <= 0 {
-                            return Ok(sort_by_column_str($dsl_table.into_boxed(), column.as_str(), &sort)?.limit(**count * pages));
-                        }
-                        Ok(sort_by_column_str($dsl_table.into_boxed(), column.as_str(), &sort)?.limit(**count * pages).offset(**count * start))
-                    }
-                }
-            }
-        }
-
-        fn sort_by_column<U: 'static + Send + Sync + AppearsOnTable<$table>>(
-            query: $boxed,
-            column: U,
-            order: &SortOrder,
-        ) -> $boxed
-        where
-            U: ExpressionMethods + QueryFragment<Pg>,
-        {
-            match order {
-                SortOrder::Asc => query.order(column.asc()),
-                SortOrder::Desc => query.order(column.desc()),
-            }
-        }
+        impl $type for $crate::repositories::query_config::QueryConfig {
+            fn get_query(&self) -> Result<$boxed, petompp_web_models::error::Error> {
+                use $crate::repositories::query_config::{QueryConfigError, SortOrder};
+                use diesel::{ExpressionMethods, QueryDsl};
 
-        fn sort_by_column_str(
-            query: $boxed,
-            column: &str,
-            order: &SortOrder,
-        ) -> Result<$boxed, Error> {
-            match column {
-                $(
-                    $name => Ok(sort_by_column(query, $column, order)),
-                )*
-                _ => {
-                    return Err(Error::Validation(ValidationError::Query(
-                        QueryValidationError::InvalidColumn(column.to_string()),
-                    )))
+                let pagination = self.pagination()?;
+                let mut query: $boxed = $dsl_table.into_boxed();
+                for (column, order) in self.sort_columns()? {
+                    query = match (column.as_str(), order) {
+                        $(
+                            ($name, SortOrder::Asc) => query.then_order_by($column.asc()),
+                            ($name, SortOrder::Desc) => query.then_order_by($column.desc()),
+                        )*
+                        _ => return Err(QueryConfigError::InvalidColumn(column).into()),
+                    };
+                }
+                query = query.then_order_by($primary_key.asc());
+                if let Some(limit) = pagination.limit {
+                    query = query.limit(limit);
+                }
+                if pagination.offset > 0 {
+                    query = query.offset(pagination.offset);
                 }
+                Ok(query)
             }
         }
     };
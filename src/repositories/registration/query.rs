@@ -0,0 +1,16 @@
+use crate::{impl_query_config, schema::user_registrations};
+use diesel::pg::Pg;
+
+impl_query_config!(
+    user_registrations::dsl::user_registrations,
+    user_registrations::BoxedQuery<'static, Pg>,
+    RegistrationsQuery,
+    user_registrations::id,
+    [
+        (user_registrations::id, "id"),
+        (user_registrations::name, "name"),
+        (user_registrations::status, "status"),
+        (user_registrations::created_at, "created_at"),
+        (user_registrations::reviewed_at, "reviewed_at"),
+    ]
+);
@@ -0,0 +1,169 @@
+use super::query::RegistrationsQuery;
+use crate::{
+    models::registration::{Registration, RegistrationStatus},
+    repositories::query_config::QueryConfig,
+    schema::{user_registrations, users},
+    PgPool,
+};
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, QueryResult,
+    RunQueryDsl,
+};
+use petompp_web_models::error::Error;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+pub trait RegistrationRepo: Send + Sync {
+    fn get_all(
+        &self,
+        status: RegistrationStatus,
+        query_config: &QueryConfig,
+    ) -> Result<Vec<Registration>, Error>;
+    fn get_by_user(&self, user_id: i32) -> Result<Vec<Registration>, Error>;
+    fn review(
+        &self,
+        id: i32,
+        status: RegistrationStatus,
+        reviewer: i32,
+        reason: Option<String>,
+    ) -> Result<Option<Registration>, Error>;
+    fn expire_stale(&self, older_than: chrono::NaiveDateTime) -> Result<usize, Error>;
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for &'r dyn RegistrationRepo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        let pool = request
+            .guard::<&rocket::State<&dyn RegistrationRepo>>()
+            .await
+            .unwrap();
+        Outcome::Success(*pool.inner())
+    }
+}
+
+impl RegistrationRepo for PgPool {
+    fn get_all(
+        &self,
+        status: RegistrationStatus,
+        query_config: &QueryConfig,
+    ) -> Result<Vec<Registration>, Error> {
+        let mut conn = self.get()?;
+        Ok(query_config
+            .get_query()?
+            .filter(user_registrations::status.eq(status))
+            .load::<Registration>(&mut conn)?)
+    }
+
+    fn get_by_user(&self, user_id: i32) -> Result<Vec<Registration>, Error> {
+        let mut conn = self.get()?;
+        Ok(user_registrations::dsl::user_registrations
+            .filter(user_registrations::user_id.eq(user_id))
+            .order(user_registrations::created_at.asc())
+            .load::<Registration>(&mut conn)?)
+    }
+
+    fn review(
+        &self,
+        id: i32,
+        status: RegistrationStatus,
+        reviewer: i32,
+        reason: Option<String>,
+    ) -> Result<Option<Registration>, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let Some(registration) = user_registrations::dsl::user_registrations
+                .filter(user_registrations::id.eq(id))
+                .for_update()
+                .first::<Registration>(conn)
+                .optional()?
+            else {
+                return Ok(None);
+            };
+            if registration.status != RegistrationStatus::Pending {
+                return Err(Error::Status(
+                    Status::Conflict.code,
+                    "Registration was already reviewed".to_string(),
+                ));
+            }
+            Ok(close(conn, &[id], status, Some(reviewer), reason)?.pop())
+        })
+    }
+
+    fn expire_stale(&self, older_than: chrono::NaiveDateTime) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let user_ids = user_registrations::dsl::user_registrations
+                .filter(user_registrations::status.eq(RegistrationStatus::Pending))
+                .filter(user_registrations::created_at.lt(older_than))
+                .select(user_registrations::user_id)
+                .load::<Option<i32>>(conn)?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            Ok(resolve_pending(
+                conn,
+                &user_ids,
+                RegistrationStatus::Expired,
+                None,
+                Some("Registration was not reviewed in time".to_string()),
+            )?
+            .len())
+        })
+    }
+}
+
+/// Closes pending registrations of the given users.
+pub fn resolve_pending(
+    conn: &mut PgConnection,
+    user_ids: &[i32],
+    status: RegistrationStatus,
+    reviewer: Option<i32>,
+    reason: Option<String>,
+) -> QueryResult<Vec<Registration>> {
+    let ids = user_registrations::dsl::user_registrations
+        .filter(user_registrations::user_id.eq_any(user_ids))
+        .filter(user_registrations::status.eq(RegistrationStatus::Pending))
+        .select(user_registrations::id)
+        .load::<i32>(conn)?;
+    close(conn, &ids, status, reviewer, reason)
+}
+
+/// Approved users get confirmed, rejected and expired ones are removed, so their names can be taken again.
+fn close(
+    conn: &mut PgConnection,
+    ids: &[i32],
+    status: RegistrationStatus,
+    reviewer: Option<i32>,
+    reason: Option<String>,
+) -> QueryResult<Vec<Registration>> {
+    let registrations = diesel::update(
+        user_registrations::dsl::user_registrations.filter(user_registrations::id.eq_any(ids)),
+    )
+    .set((
+        user_registrations::status.eq(status),
+        user_registrations::reason.eq(reason),
+        user_registrations::reviewed_by.eq(reviewer),
+        user_registrations::reviewed_at.eq(chrono::Utc::now().naive_utc()),
+    ))
+    .get_results::<Registration>(conn)?;
+    let user_ids = registrations
+        .iter()
+        .filter_map(|r| r.user_id)
+        .collect::<Vec<_>>();
+    let target = users::dsl::users
+        .filter(users::id.eq_any(user_ids))
+        .filter(users::confirmed.eq(false));
+    match status {
+        RegistrationStatus::Pending => {}
+        RegistrationStatus::Approved => {
+            diesel::update(target)
+                .set(users::confirmed.eq(true))
+                .execute(conn)?;
+        }
+        RegistrationStatus::Rejected | RegistrationStatus::Expired => {
+            diesel::delete(target).execute(conn)?;
+        }
+    }
+    Ok(registrations)
+}
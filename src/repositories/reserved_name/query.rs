@@ -0,0 +1,15 @@
+use crate::{impl_query_config, schema::reserved_names};
+use diesel::pg::Pg;
+
+impl_query_config!(
+    reserved_names::dsl::reserved_names,
+    reserved_names::BoxedQuery<'static, Pg>,
+    ReservedNamesQuery,
+    reserved_names::id,
+    [
+        (reserved_names::id, "id"),
+        (reserved_names::pattern, "pattern"),
+        (reserved_names::kind, "kind"),
+        (reserved_names::created_at, "created_at"),
+    ]
+);
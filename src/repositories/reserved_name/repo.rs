@@ -0,0 +1,70 @@
+use super::query::ReservedNamesQuery;
+use crate::{
+    models::reserved_name::ReservedName, repositories::query_config::QueryConfig,
+    schema::reserved_names, PgPool,
+};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use petompp_web_models::error::Error;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+pub trait ReservedNameRepo: Send + Sync {
+    fn get_all(&self, query_config: &QueryConfig) -> Result<Vec<ReservedName>, Error>;
+    fn create(&self, reserved_name: &ReservedName) -> Result<ReservedName, Error>;
+    fn delete(&self, id: i32) -> Result<Option<ReservedName>, Error>;
+    fn find_match(&self, name: &str) -> Result<Option<ReservedName>, Error>;
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for &'r dyn ReservedNameRepo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        let pool = request
+            .guard::<&rocket::State<&dyn ReservedNameRepo>>()
+            .await
+            .unwrap();
+        Outcome::Success(*pool.inner())
+    }
+}
+
+impl ReservedNameRepo for PgPool {
+    fn get_all(&self, query_config: &QueryConfig) -> Result<Vec<ReservedName>, Error> {
+        let mut conn = self.get()?;
+        Ok(query_config.get_query()?.load::<ReservedName>(&mut conn)?)
+    }
+
+    fn create(&self, reserved_name: &ReservedName) -> Result<ReservedName, Error> {
+        let mut conn = self.get()?;
+        diesel::insert_into(reserved_names::dsl::reserved_names)
+            .values(reserved_name)
+            .get_result::<ReservedName>(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::UniqueViolation,
+                    _,
+                ) => Error::Status(
+                    Status::Conflict.code,
+                    format!("Name pattern {} already exists", reserved_name.pattern),
+                ),
+                e => e.into(),
+            })
+    }
+
+    fn delete(&self, id: i32) -> Result<Option<ReservedName>, Error> {
+        let mut conn = self.get()?;
+        Ok(
+            diesel::delete(reserved_names::dsl::reserved_names.filter(reserved_names::id.eq(id)))
+                .get_result::<ReservedName>(&mut conn)
+                .optional()?,
+        )
+    }
+
+    fn find_match(&self, name: &str) -> Result<Option<ReservedName>, Error> {
+        let mut conn = self.get()?;
+        // The list is expected to stay small, so it is matched in memory
+        Ok(reserved_names::dsl::reserved_names
+            .load::<ReservedName>(&mut conn)?
+            .into_iter()
+            .find(|r| r.matches(name)))
+    }
+}
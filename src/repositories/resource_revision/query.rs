@@ -0,0 +1,15 @@
+use crate::{impl_query_config, schema::resource_revisions};
+use diesel::pg::Pg;
+
+impl_query_config!(
+    resource_revisions::dsl::resource_revisions,
+    resource_revisions::BoxedQuery<'static, Pg>,
+    ResourceRevisionsQuery,
+    resource_revisions::id,
+    [
+        (resource_revisions::id, "id"),
+        (resource_revisions::lang, "lang"),
+        (resource_revisions::author, "author"),
+        (resource_revisions::created_at, "created_at"),
+    ]
+);
@@ -0,0 +1,72 @@
+use super::query::ResourceRevisionsQuery;
+use crate::{
+    models::{lang::Lang, resource_revision::ResourceRevision},
+    repositories::query_config::QueryConfig,
+    schema::resource_revisions,
+    PgPool,
+};
+use diesel::{
+    ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, QueryResult, RunQueryDsl,
+};
+use petompp_web_models::error::Error;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+pub trait ResourceRevisionRepo: Send + Sync {
+    fn get_all(
+        &self,
+        key: &str,
+        lang: Option<&Lang>,
+        query_config: &QueryConfig,
+    ) -> Result<Vec<ResourceRevision>, Error>;
+    fn get(&self, key: &str, id: i32) -> Result<Option<ResourceRevision>, Error>;
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for &'r dyn ResourceRevisionRepo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        let pool = request
+            .guard::<&rocket::State<&dyn ResourceRevisionRepo>>()
+            .await
+            .unwrap();
+        Outcome::Success(*pool.inner())
+    }
+}
+
+impl ResourceRevisionRepo for PgPool {
+    fn get_all(
+        &self,
+        key: &str,
+        lang: Option<&Lang>,
+        query_config: &QueryConfig,
+    ) -> Result<Vec<ResourceRevision>, Error> {
+        let mut conn = self.get()?;
+        let mut query = query_config
+            .get_query()?
+            .filter(resource_revisions::key.eq(key.to_string()));
+        if let Some(lang) = lang {
+            query = query.filter(resource_revisions::lang.eq(lang.to_string()));
+        }
+        Ok(query.load::<ResourceRevision>(&mut conn)?)
+    }
+
+    fn get(&self, key: &str, id: i32) -> Result<Option<ResourceRevision>, Error> {
+        let mut conn = self.get()?;
+        Ok(find(&mut conn, key, id)?)
+    }
+}
+
+pub fn find(conn: &mut PgConnection, key: &str, id: i32) -> QueryResult<Option<ResourceRevision>> {
+    resource_revisions::dsl::resource_revisions
+        .filter(resource_revisions::key.eq(key))
+        .filter(resource_revisions::id.eq(id))
+        .first::<ResourceRevision>(conn)
+        .optional()
+}
+
+pub fn record(conn: &mut PgConnection, revisions: &[ResourceRevision]) -> QueryResult<usize> {
+    diesel::insert_into(resource_revisions::dsl::resource_revisions)
+        .values(revisions)
+        .execute(conn)
+}
@@ -0,0 +1,213 @@
+use crate::{
+    models::{
+        resource_data::ResourceValue,
+        resource_variant::{ResourceVariant, ResourceVariantDto},
+    },
+    repositories::resources::repo::check_values,
+    schema::{resource_variants, resources},
+    PgPool,
+};
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, OptionalExtension, PgConnection,
+    PgSortExpressionMethods, QueryDsl, QueryResult, RunQueryDsl,
+};
+use petompp_web_models::error::Error;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+pub trait ResourceVariantRepo: Send + Sync {
+    fn get_all(&self, key: &str) -> Result<Vec<ResourceVariant>, Error>;
+    fn last_boundary(
+        &self,
+        key: &str,
+        at: chrono::NaiveDateTime,
+    ) -> Result<Option<chrono::NaiveDateTime>, Error>;
+    fn create(
+        &self,
+        key: &str,
+        variant: ResourceVariantDto,
+        author: i32,
+    ) -> Result<ResourceVariant, Error>;
+    fn update(
+        &self,
+        key: &str,
+        id: i32,
+        variant: ResourceVariantDto,
+        author: i32,
+    ) -> Result<Option<ResourceVariant>, Error>;
+    fn delete(&self, key: &str, id: i32) -> Result<usize, Error>;
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for &'r dyn ResourceVariantRepo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        request
+            .guard::<&rocket::State<&dyn ResourceVariantRepo>>()
+            .await
+            .map(|pool| *pool.inner())
+    }
+}
+
+impl ResourceVariantRepo for PgPool {
+    fn get_all(&self, key: &str) -> Result<Vec<ResourceVariant>, Error> {
+        let mut conn = self.get()?;
+        Ok(resource_variants::dsl::resource_variants
+            .filter(resource_variants::key.eq(key))
+            .order((
+                resource_variants::lang.asc(),
+                resource_variants::valid_from.asc().nulls_first(),
+            ))
+            .load::<ResourceVariant>(&mut conn)?)
+    }
+
+    fn last_boundary(
+        &self,
+        key: &str,
+        at: chrono::NaiveDateTime,
+    ) -> Result<Option<chrono::NaiveDateTime>, Error> {
+        let mut conn = self.get()?;
+        Ok(resource_variants::dsl::resource_variants
+            .filter(resource_variants::key.eq(key))
+            .load::<ResourceVariant>(&mut conn)?
+            .iter()
+            .filter_map(|v| v.passed_boundary(at))
+            .max())
+    }
+
+    fn create(
+        &self,
+        key: &str,
+        variant: ResourceVariantDto,
+        author: i32,
+    ) -> Result<ResourceVariant, Error> {
+        variant.validate()?;
+        let variant = variant.into_variant(key, author);
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            check_window(conn, &variant)?;
+            diesel::insert_into(resource_variants::dsl::resource_variants)
+                .values(&variant)
+                .get_result::<ResourceVariant>(conn)
+                .map_err(map_language_error)
+        })
+    }
+
+    fn update(
+        &self,
+        key: &str,
+        id: i32,
+        variant: ResourceVariantDto,
+        author: i32,
+    ) -> Result<Option<ResourceVariant>, Error> {
+        variant.validate()?;
+        let variant = ResourceVariant {
+            id: Some(id),
+            ..variant.into_variant(key, author)
+        };
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            check_window(conn, &variant)?;
+            diesel::update(
+                resource_variants::dsl::resource_variants
+                    .filter(resource_variants::key.eq(key))
+                    .filter(resource_variants::id.eq(id)),
+            )
+            .set((
+                resource_variants::lang.eq(&variant.lang),
+                resource_variants::value.eq(&variant.value),
+                resource_variants::valid_from.eq(variant.valid_from),
+                resource_variants::valid_until.eq(variant.valid_until),
+                resource_variants::author.eq(variant.author),
+            ))
+            .get_result::<ResourceVariant>(conn)
+            .optional()
+            .map_err(map_language_error)
+        })
+    }
+
+    fn delete(&self, key: &str, id: i32) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        Ok(diesel::delete(
+            resource_variants::dsl::resource_variants
+                .filter(resource_variants::key.eq(key))
+                .filter(resource_variants::id.eq(id)),
+        )
+        .execute(&mut conn)?)
+    }
+}
+
+/// Variants of the keys and languages whose window contains `at`.
+pub fn active(
+    conn: &mut PgConnection,
+    keys: &[&str],
+    langs: &[String],
+    at: chrono::NaiveDateTime,
+) -> QueryResult<Vec<ResourceVariant>> {
+    resource_variants::dsl::resource_variants
+        .filter(resource_variants::key.eq_any(keys))
+        .filter(resource_variants::lang.eq_any(langs))
+        .filter(
+            resource_variants::valid_from
+                .is_null()
+                .or(resource_variants::valid_from.le(at)),
+        )
+        .filter(
+            resource_variants::valid_until
+                .is_null()
+                .or(resource_variants::valid_until.gt(at)),
+        )
+        .order(resource_variants::id.asc())
+        .load::<ResourceVariant>(conn)
+}
+
+/// Windows of the same language must not overlap, so at most one variant is active at a time.
+///
+/// Locks the resource so concurrent changes can't introduce an overlap, resources in the trash are not found.
+fn check_window(conn: &mut PgConnection, variant: &ResourceVariant) -> Result<(), Error> {
+    resources::dsl::resources
+        .filter(resources::key.eq(&variant.key))
+        .filter(resources::deleted_at.is_null())
+        .for_update()
+        .select(resources::key)
+        .first::<String>(conn)?;
+    check_values(
+        conn,
+        &variant.key,
+        &[ResourceValue {
+            key: variant.key.clone(),
+            lang: variant.lang.clone(),
+            value: variant.value.clone(),
+        }],
+    )?;
+    let existing = resource_variants::dsl::resource_variants
+        .filter(resource_variants::key.eq(&variant.key))
+        .filter(resource_variants::lang.eq(&variant.lang))
+        .load::<ResourceVariant>(conn)?;
+    match existing
+        .iter()
+        .find(|v| v.id != variant.id && v.overlaps(variant))
+    {
+        Some(other) => Err(Error::Status(
+            Status::Conflict.code,
+            format!(
+                "Window overlaps with variant {}",
+                other.id.unwrap_or_default()
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn map_language_error(e: diesel::result::Error) -> Error {
+    match e {
+        diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        ) => Error::Status(
+            Status::BadRequest.code,
+            "Language is not registered".to_string(),
+        ),
+        e => e.into(),
+    }
+}
@@ -0,0 +1,13 @@
+use crate::{impl_query_config, schema::resources};
+use diesel::pg::Pg;
+
+impl_query_config!(
+    resources::dsl::resources,
+    resources::BoxedQuery<'static, Pg>,
+    ResourcesQuery,
+    resources::key,
+    [
+        (resources::key, "key"),
+        (resources::deleted_at, "deleted_at"),
+    ]
+);
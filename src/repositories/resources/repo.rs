@@ -1,18 +1,143 @@
-use crate::{models::resource_data::Resource, schema::resources, PgPool};
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
-use petompp_web_models::{
-    error::{Error, ResourceDataValidationError, ValidationError},
-    models::country::Country,
+use super::query::ResourcesQuery;
+use crate::{
+    models::{
+        lang::{Lang, BASE_LANG},
+        message_format::Message,
+        preconditions::Preconditions,
+        resource_coverage::LanguageCoverage,
+        resource_data::{
+            Resource, ResourceContentType, ResourceMetaDto, ResourceSelection, ResourceValue,
+            SearchResult, ServedValue,
+        },
+        resource_draft::ResourceDraft,
+        resource_revision::ResourceRevision,
+        resource_transfer::{ImportDiff, ValueRef},
+        resource_variant::ResourceVariant,
+    },
+    repositories::{
+        language::repo::fallback_chain,
+        query_config::QueryConfig,
+        resource_revision::repo::{find, record},
+        resource_variant::repo::active,
+    },
+    schema::{
+        languages, resource_drafts, resource_revisions, resource_values, resource_variants,
+        resources,
+    },
+    PgPool,
 };
+use diesel::{
+    sql_types::{BigInt, Nullable, Text, Varchar},
+    upsert::excluded,
+    Connection, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, QueryResult,
+    RunQueryDsl, TextExpressionMethods,
+};
+use petompp_web_models::error::{Error, ResourceDataValidationError, ValidationError};
 use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+const KEY_MAX_LENGTH: usize = 64;
+const SEARCH_LIMIT: i64 = 20;
+/// Most keys a prefix may select at once, like the keys listed in a batch.
+const MAX_PREFIX_KEYS: i64 = 100;
+
+/// Matches the indexed values with the text search configuration of their language.
+///
+/// Values are HTML-escaped before highlighting, so the `<mark>` tags are the only markup in snippets.
+const SEARCH_QUERY: &str = "
+SELECT v.key, v.lang,
+    ts_headline(l.search_config::regconfig,
+        replace(replace(replace(replace(replace(v.value,
+            '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '\"', '&quot;'), '''', '&#39;'),
+        q.query,
+        'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=20, MinWords=5') AS snippet,
+    ts_rank(s.document, q.query) AS rank
+FROM resource_search s
+JOIN resource_values v ON v.key = s.key AND v.lang = s.lang
+JOIN resources r ON r.key = s.key AND r.deleted_at IS NULL
+JOIN languages l ON l.code = s.lang
+CROSS JOIN LATERAL websearch_to_tsquery(l.search_config::regconfig, $1) AS q(query)
+WHERE s.document @@ q.query AND ($2 IS NULL OR s.lang = $2)
+ORDER BY rank DESC, v.key, v.lang
+LIMIT $3 OFFSET $4";
+
+/// Keys of the resources that are not in the trash.
+type ActiveKeys = diesel::dsl::Select<
+    diesel::dsl::Filter<resources::table, diesel::dsl::IsNull<resources::deleted_at>>,
+    resources::key,
+>;
 
 pub trait ResourcesRepo: Send + Sync {
-    fn get(&self, key: &str, lang: &Country) -> Result<(Country, String), Error>;
-    fn get_all(&self) -> Result<Vec<Resource>, Error>;
-    fn create(&self, data: &Resource) -> Result<Resource, Error>;
-    fn update(&self, data: &Resource) -> Result<Resource, Error>;
-    fn delete(&self, key: &str) -> Result<(), Error>;
-    fn delete_lang(&self, key: &str, lang: &Country) -> Result<(), Error>;
+    fn get(&self, key: &str, lang: &Lang, draft: bool) -> Result<ServedValue, Error>;
+    fn get_many(
+        &self,
+        selection: &ResourceSelection,
+        lang: &Lang,
+    ) -> Result<BTreeMap<String, ServedValue>, Error>;
+    fn get_all(&self, query_config: &QueryConfig) -> Result<Vec<Resource>, Error>;
+    fn get_values(&self, key: &str) -> Result<Vec<ResourceValue>, Error>;
+    fn search(
+        &self,
+        text: &str,
+        lang: Option<&Lang>,
+        query_config: &QueryConfig,
+    ) -> Result<Vec<SearchResult>, Error>;
+    fn get_drafts(&self, key: &str) -> Result<Vec<ResourceDraft>, Error>;
+    fn coverage(
+        &self,
+        prefix: Option<&str>,
+        langs: &[Lang],
+    ) -> Result<Vec<LanguageCoverage>, Error>;
+    fn get_values_in(
+        &self,
+        prefix: Option<&str>,
+        langs: &[Lang],
+    ) -> Result<Vec<ResourceValue>, Error>;
+    fn create(
+        &self,
+        key: &str,
+        values: &[ResourceValue],
+        author: i32,
+    ) -> Result<Vec<ResourceValue>, Error>;
+    fn get_resource(&self, key: &str) -> Result<Option<Resource>, Error>;
+    fn update_meta(&self, key: &str, meta: &ResourceMetaDto) -> Result<Resource, Error>;
+    fn rename(&self, key: &str, new_key: &str) -> Result<Resource, Error>;
+    /// Saves the values as drafts, returns the resource with them applied and its new version.
+    fn update(
+        &self,
+        key: &str,
+        values: &[ResourceValue],
+        author: i32,
+        preconditions: &Preconditions,
+    ) -> Result<(Vec<ResourceValue>, chrono::NaiveDateTime), Error>;
+    fn publish(
+        &self,
+        key: &str,
+        langs: &[Lang],
+        publish_at: Option<chrono::NaiveDateTime>,
+    ) -> Result<Vec<ResourceDraft>, Error>;
+    fn publish_scheduled(&self) -> Result<usize, Error>;
+    fn discard_drafts(&self, key: &str, langs: &[Lang]) -> Result<usize, Error>;
+    fn restore(
+        &self,
+        key: &str,
+        revision: i32,
+        author: i32,
+    ) -> Result<Option<Vec<ResourceValue>>, Error>;
+    fn delete(&self, key: &str, author: i32) -> Result<Option<Resource>, Error>;
+    fn get_trash(&self, query_config: &QueryConfig) -> Result<Vec<Resource>, Error>;
+    fn restore_from_trash(&self, key: &str) -> Result<Option<Resource>, Error>;
+    fn purge(&self, key: &str) -> Result<usize, Error>;
+    fn purge_deleted(&self, older_than: chrono::NaiveDateTime) -> Result<usize, Error>;
+    fn delete_lang(&self, key: &str, lang: &Lang, author: i32) -> Result<(), Error>;
+    fn import(
+        &self,
+        values: &[ResourceValue],
+        prefix: Option<&str>,
+        langs: &[Lang],
+        apply: bool,
+        author: i32,
+    ) -> Result<ImportDiff, Error>;
 }
 
 #[async_trait]
@@ -28,84 +153,973 @@ impl<'r> FromRequest<'r> for &'r dyn ResourcesRepo {
 }
 
 impl ResourcesRepo for PgPool {
-    fn get(&self, key: &str, lang: &Country) -> Result<(Country, String), Error> {
-        let mut conn = self.get()?;
-        let q = resources::dsl::resources.filter(resources::key.eq(key));
-        Ok(match lang {
-            Country::Poland => q
-                .select((resources::pl, resources::en))
-                .get_result::<(Option<String>, String)>(&mut conn)
-                .map(|(pl, en)| match pl {
-                    Some(pl) => (Country::Poland, pl),
-                    None => (Country::UnitedKingdom, en),
-                })?,
-            _ => q
-                .select(resources::en)
-                .get_result::<String>(&mut conn)
-                .map(|en| (Country::UnitedKingdom, en))?,
+    fn get(&self, key: &str, lang: &Lang, draft: bool) -> Result<ServedValue, Error> {
+        let mut conn = self.get()?;
+        let chain = fallback_chain(&mut conn, lang)?;
+        let mut values = resource_values::dsl::resource_values
+            .filter(resource_values::key.eq(key))
+            .filter(resource_values::lang.eq_any(chain.iter().map(|l| l.to_string())))
+            .load::<ResourceValue>(&mut conn)?;
+        // Drafts preview the values being edited, so active variants are left out of them
+        let variants = match draft {
+            true => {
+                let drafts = resource_drafts::dsl::resource_drafts
+                    .filter(resource_drafts::key.eq(key))
+                    .filter(resource_drafts::lang.eq_any(chain.iter().map(|l| l.to_string())))
+                    .load::<ResourceDraft>(&mut conn)?;
+                overlay_drafts(&mut values, drafts);
+                Vec::new()
+            }
+            false => {
+                let langs = chain.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+                let variants = active(&mut conn, &[key], &langs, chrono::Utc::now().naive_utc())?;
+                overlay_variants(&mut values, &variants);
+                variants
+            }
+        };
+        let (served, value) = chain
+            .into_iter()
+            .find_map(|l| {
+                let value = values.iter().find(|v| v.lang == *l)?;
+                Some((l, value.value.clone()))
+            })
+            .ok_or(diesel::result::Error::NotFound)?;
+        let content_type = resources::dsl::resources
+            .filter(resources::key.eq(key))
+            .filter(resources::deleted_at.is_null())
+            .select(resources::content_type)
+            .first::<ResourceContentType>(&mut conn)?;
+        Ok(ServedValue {
+            fallback: &served != lang,
+            variant: variants
+                .iter()
+                .find(|v| v.lang == *served)
+                .and_then(|v| v.id),
+            lang: served,
+            value,
+            content_type,
         })
     }
 
-    fn get_all(&self) -> Result<Vec<Resource>, Error> {
+    fn get_many(
+        &self,
+        selection: &ResourceSelection,
+        lang: &Lang,
+    ) -> Result<BTreeMap<String, ServedValue>, Error> {
         let mut conn = self.get()?;
-        let res = resources::dsl::resources.load::<Resource>(&mut conn)?;
-        Ok(res)
+        let chain = fallback_chain(&mut conn, lang)?;
+        let selected = match selection {
+            ResourceSelection::Keys(keys) => keys.clone(),
+            ResourceSelection::Prefix(prefix) => {
+                let keys = resources::dsl::resources
+                    .filter(resources::deleted_at.is_null())
+                    .filter(resources::key.like(prefix_pattern(prefix)))
+                    .select(resources::key)
+                    .order(resources::key.asc())
+                    .limit(MAX_PREFIX_KEYS + 1)
+                    .load::<String>(&mut conn)?;
+                if keys.len() as i64 > MAX_PREFIX_KEYS {
+                    return Err(Error::Status(
+                        Status::BadRequest.code,
+                        format!("Prefix matches more than {} keys", MAX_PREFIX_KEYS),
+                    ));
+                }
+                keys
+            }
+        };
+        let mut loaded = resource_values::dsl::resource_values
+            .filter(resource_values::lang.eq_any(chain.iter().map(|l| l.to_string())))
+            .filter(resource_values::key.eq_any(active_keys()))
+            .filter(resource_values::key.eq_any(&selected))
+            .load::<ResourceValue>(&mut conn)?;
+        let keys = loaded
+            .iter()
+            .map(|v| v.key.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let langs = chain.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        let variants = active(&mut conn, &keys, &langs, chrono::Utc::now().naive_utc())?;
+        overlay_variants(&mut loaded, &variants);
+        // Keep the value of the language that comes first in the chain
+        let mut values = BTreeMap::<String, (usize, ResourceValue)>::new();
+        for value in loaded {
+            let Some(rank) = chain.iter().position(|l| value.lang == **l) else {
+                continue;
+            };
+            if values.get(&value.key).is_none_or(|(r, _)| rank < *r) {
+                values.insert(value.key.clone(), (rank, value));
+            }
+        }
+        let content_types = resources::dsl::resources
+            .filter(resources::key.eq_any(values.keys()))
+            .select((resources::key, resources::content_type))
+            .load::<(String, ResourceContentType)>(&mut conn)?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        Ok(values
+            .into_iter()
+            .map(|(key, (rank, value))| {
+                let variant = variants
+                    .iter()
+                    .find(|v| v.key == key && v.lang == *chain[rank])
+                    .and_then(|v| v.id);
+                let served = ServedValue {
+                    variant,
+                    lang: chain[rank].clone(),
+                    value: value.value,
+                    fallback: rank > 0,
+                    content_type: content_types.get(&key).copied().unwrap_or_default(),
+                };
+                (key, served)
+            })
+            .collect())
     }
 
-    fn create(&self, data: &Resource) -> Result<Resource, Error> {
+    fn get_all(&self, query_config: &QueryConfig) -> Result<Vec<Resource>, Error> {
         let mut conn = self.get()?;
-        let res = diesel::insert_into(resources::dsl::resources)
-            .values(data)
-            .get_result::<Resource>(&mut conn)?;
+        let res = query_config
+            .get_query()?
+            .filter(resources::deleted_at.is_null())
+            .load::<Resource>(&mut conn)?;
         Ok(res)
     }
 
-    fn update(&self, data: &Resource) -> Result<Resource, Error> {
+    fn get_values(&self, key: &str) -> Result<Vec<ResourceValue>, Error> {
+        let mut conn = self.get()?;
+        Ok(load_values(&mut conn, key)?)
+    }
+
+    fn search(
+        &self,
+        text: &str,
+        lang: Option<&Lang>,
+        query_config: &QueryConfig,
+    ) -> Result<Vec<SearchResult>, Error> {
+        let pagination = query_config.pagination()?;
+        let mut conn = self.get()?;
+        Ok(diesel::sql_query(SEARCH_QUERY)
+            .bind::<Text, _>(text)
+            .bind::<Nullable<Varchar>, _>(lang.map(|l| l.to_string()))
+            .bind::<BigInt, _>(pagination.limit.unwrap_or(SEARCH_LIMIT))
+            .bind::<BigInt, _>(pagination.offset)
+            .load::<SearchResult>(&mut conn)?)
+    }
+
+    fn coverage(
+        &self,
+        prefix: Option<&str>,
+        langs: &[Lang],
+    ) -> Result<Vec<LanguageCoverage>, Error> {
+        let mut conn = self.get()?;
+        let langs = match langs.is_empty() {
+            true => languages::dsl::languages
+                .select(languages::code)
+                .filter(languages::code.ne(BASE_LANG))
+                .order(languages::code.asc())
+                .load::<String>(&mut conn)?
+                .iter()
+                .filter_map(|code| Lang::new(code))
+                .collect(),
+            false => langs
+                .iter()
+                .filter(|l| !l.is_base())
+                .cloned()
+                .collect::<Vec<_>>(),
+        };
+        let mut all_langs = langs.clone();
+        all_langs.push(Lang::base());
+        let values = load_values_in(&mut conn, prefix, &all_langs)?;
+        // Values are outdated when the base value was revised after them
+        let revised = resource_revisions::dsl::resource_revisions
+            .filter(resource_revisions::lang.eq_any(all_langs.iter().map(|l| l.to_string())))
+            .group_by((resource_revisions::key, resource_revisions::lang))
+            .select((
+                resource_revisions::key,
+                resource_revisions::lang,
+                diesel::dsl::max(resource_revisions::created_at),
+            ))
+            .load::<(String, String, Option<chrono::NaiveDateTime>)>(&mut conn)?
+            .into_iter()
+            .map(|(key, lang, at)| ((key, lang), at))
+            .collect::<HashMap<_, _>>();
+        let revised_at = |key: &str, lang: &str| {
+            revised
+                .get(&(key.to_string(), lang.to_string()))
+                .copied()
+                .flatten()
+        };
+        let base_keys = values
+            .iter()
+            .filter(|v| v.lang == BASE_LANG)
+            .map(|v| v.key.as_str())
+            .collect::<Vec<_>>();
+        Ok(langs
+            .iter()
+            .map(|lang| {
+                let mut missing = Vec::new();
+                let mut outdated = Vec::new();
+                for key in &base_keys {
+                    if !values.iter().any(|v| v.key == *key && v.lang == **lang) {
+                        missing.push(key.to_string());
+                    } else if revised_at(key, BASE_LANG) > revised_at(key, lang) {
+                        outdated.push(key.to_string());
+                    }
+                }
+                LanguageCoverage::new(lang.to_string(), base_keys.len(), missing, outdated)
+            })
+            .collect())
+    }
+
+    fn get_drafts(&self, key: &str) -> Result<Vec<ResourceDraft>, Error> {
+        let mut conn = self.get()?;
+        Ok(resource_drafts::dsl::resource_drafts
+            .filter(resource_drafts::key.eq(key))
+            .order(resource_drafts::lang.asc())
+            .load::<ResourceDraft>(&mut conn)?)
+    }
+
+    fn get_values_in(
+        &self,
+        prefix: Option<&str>,
+        langs: &[Lang],
+    ) -> Result<Vec<ResourceValue>, Error> {
         let mut conn = self.get()?;
-        let key = data
-            .key
-            .clone()
-            .ok_or(Error::Validation(ValidationError::ResourceData(
+        Ok(load_values_in(&mut conn, prefix, langs)?)
+    }
+
+    fn create(
+        &self,
+        key: &str,
+        values: &[ResourceValue],
+        author: i32,
+    ) -> Result<Vec<ResourceValue>, Error> {
+        if !values.iter().any(|v| v.lang == BASE_LANG) {
+            return Err(Error::Validation(ValidationError::ResourceData(
+                ResourceDataValidationError::ValueMissing,
+            )));
+        }
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            diesel::insert_into(resources::dsl::resources)
+                .values(&Resource {
+                    key: key.to_string(),
+                    ..Default::default()
+                })
+                .execute(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    ) => Error::Status(
+                        Status::Conflict.code,
+                        format!("Resource {} already exists or is in the trash", key),
+                    ),
+                    e => e.into(),
+                })?;
+            save_drafts(conn, key, values, author)
+        })
+    }
+
+    fn get_resource(&self, key: &str) -> Result<Option<Resource>, Error> {
+        let mut conn = self.get()?;
+        Ok(resources::dsl::resources
+            .filter(resources::key.eq(key))
+            .filter(resources::deleted_at.is_null())
+            .first::<Resource>(&mut conn)
+            .optional()?)
+    }
+
+    /// Switching to the `message` content type requires the values, drafts and variants to be valid messages.
+    fn update_meta(&self, key: &str, meta: &ResourceMetaDto) -> Result<Resource, Error> {
+        meta.validate()?;
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let resource = diesel::update(
+                resources::dsl::resources
+                    .filter(resources::key.eq(key))
+                    .filter(resources::deleted_at.is_null()),
+            )
+            .set((
+                resources::description.eq(&meta.description),
+                resources::owner.eq(meta.owner),
+                resources::content_type.eq(meta.content_type),
+                resources::max_length.eq(meta.max_length),
+                resources::updated_at.eq(diesel::dsl::now),
+            ))
+            .get_result::<Resource>(conn)
+            .map_err(|e| match e {
+                diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                    _,
+                ) => Error::Status(
+                    Status::BadRequest.code,
+                    "Owner is not a registered user".to_string(),
+                ),
+                e => e.into(),
+            })?;
+            if meta.content_type == ResourceContentType::Message {
+                let values = load_values(conn, key)?;
+                let drafts = resource_drafts::dsl::resource_drafts
+                    .filter(resource_drafts::key.eq(key))
+                    .select((resource_drafts::lang, resource_drafts::value))
+                    .load::<(String, String)>(conn)?;
+                let variants = resource_variants::dsl::resource_variants
+                    .filter(resource_variants::key.eq(key))
+                    .select((resource_variants::lang, resource_variants::value))
+                    .load::<(String, String)>(conn)?;
+                check_messages(
+                    key,
+                    values
+                        .iter()
+                        .map(|v| (v.lang.as_str(), v.value.as_str()))
+                        .chain(
+                            drafts
+                                .iter()
+                                .chain(&variants)
+                                .map(|(l, v)| (l.as_str(), v.as_str())),
+                        ),
+                )?;
+            }
+            Ok(resource)
+        })
+    }
+
+    fn rename(&self, key: &str, new_key: &str) -> Result<Resource, Error> {
+        if new_key.is_empty() || new_key.chars().count() > KEY_MAX_LENGTH {
+            return Err(Error::Validation(ValidationError::ResourceData(
                 ResourceDataValidationError::KeyMissing,
-            )))?;
-        let res = match (&data.en, &data.pl) {
-            (Some(en), Some(pl)) => diesel::update(resources::dsl::resources)
-                .filter(resources::dsl::key.eq(key))
-                .set((resources::dsl::en.eq(en), resources::dsl::pl.eq(pl)))
-                .get_result::<Resource>(&mut conn)?,
-            (Some(en), None) => diesel::update(resources::dsl::resources)
-                .filter(resources::dsl::key.eq(key))
-                .set(resources::dsl::en.eq(en))
-                .get_result::<Resource>(&mut conn)?,
-            (None, Some(pl)) => diesel::update(resources::dsl::resources)
-                .filter(resources::dsl::key.eq(key))
-                .set(resources::dsl::pl.eq(pl))
-                .get_result::<Resource>(&mut conn)?,
-            _ => {
+            )));
+        }
+        let mut conn = self.get()?;
+        // Values, drafts and revisions follow the key through `ON UPDATE CASCADE`
+        diesel::update(
+            resources::dsl::resources
+                .filter(resources::key.eq(key))
+                .filter(resources::deleted_at.is_null()),
+        )
+        .set(resources::key.eq(new_key))
+        .get_result::<Resource>(&mut conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            ) => Error::Status(
+                Status::Conflict.code,
+                format!("Resource {} already exists", new_key),
+            ),
+            e => e.into(),
+        })
+    }
+
+    fn update(
+        &self,
+        key: &str,
+        values: &[ResourceValue],
+        author: i32,
+        preconditions: &Preconditions,
+    ) -> Result<(Vec<ResourceValue>, chrono::NaiveDateTime), Error> {
+        if values.is_empty() {
+            return Err(Error::Validation(ValidationError::ResourceData(
+                ResourceDataValidationError::ValueMissing,
+            )));
+        }
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let updated_at = lock_resource(conn, key)?.updated_at.unwrap_or_default();
+            if !preconditions.matches(&updated_at) {
+                return Err(Error::Status(
+                    Status::PreconditionFailed.code,
+                    "Resource was changed since it was fetched".to_string(),
+                ));
+            }
+            let values = save_drafts(conn, key, values, author)?;
+            // Read under the lock, so the version matches the values even with concurrent writes
+            let updated_at = resources::dsl::resources
+                .filter(resources::key.eq(key))
+                .select(resources::updated_at)
+                .first::<chrono::NaiveDateTime>(conn)?;
+            Ok((values, updated_at))
+        })
+    }
+
+    fn publish(
+        &self,
+        key: &str,
+        langs: &[Lang],
+        publish_at: Option<chrono::NaiveDateTime>,
+    ) -> Result<Vec<ResourceDraft>, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            lock_resource(conn, key)?;
+            let drafts = resource_drafts::dsl::resource_drafts
+                .filter(resource_drafts::key.eq(key))
+                .for_update()
+                .load::<ResourceDraft>(conn)?
+                .into_iter()
+                .filter(|d| langs.is_empty() || langs.iter().any(|l| d.lang == **l))
+                .collect::<Vec<_>>();
+            if drafts.is_empty() {
+                return Err(Error::Status(
+                    Status::NotFound.code,
+                    "No drafts to publish".to_string(),
+                ));
+            }
+            // A published resource must always have a base value
+            if !drafts.iter().any(|d| d.lang == BASE_LANG)
+                && !load_values(conn, key)?.iter().any(|v| v.lang == BASE_LANG)
+            {
                 return Err(Error::Validation(ValidationError::ResourceData(
                     ResourceDataValidationError::ValueMissing,
-                )))
+                )));
             }
-        };
-        Ok(res)
+            match publish_at.filter(|at| *at > chrono::Utc::now().naive_utc()) {
+                Some(at) => Ok(diesel::update(
+                    resource_drafts::dsl::resource_drafts
+                        .filter(resource_drafts::key.eq(key))
+                        .filter(resource_drafts::lang.eq_any(drafts.iter().map(|d| &d.lang))),
+                )
+                .set(resource_drafts::publish_at.eq(at))
+                .get_results::<ResourceDraft>(conn)?),
+                None => {
+                    publish_drafts(conn, &drafts)?;
+                    Ok(drafts)
+                }
+            }
+        })
+    }
+
+    fn publish_scheduled(&self) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let drafts = resource_drafts::dsl::resource_drafts
+                .filter(resource_drafts::publish_at.le(chrono::Utc::now().naive_utc()))
+                .filter(resource_drafts::key.eq_any(active_keys()))
+                .for_update()
+                .skip_locked()
+                .load::<ResourceDraft>(conn)?;
+            publish_drafts(conn, &drafts)?;
+            Ok(drafts.len())
+        })
+    }
+
+    fn discard_drafts(&self, key: &str, langs: &[Lang]) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        let mut query = diesel::delete(
+            resource_drafts::dsl::resource_drafts.filter(resource_drafts::key.eq(key)),
+        )
+        .into_boxed();
+        if !langs.is_empty() {
+            query = query.filter(resource_drafts::lang.eq_any(langs.iter().map(|l| l.to_string())));
+        }
+        Ok(query.execute(&mut conn)?)
+    }
+
+    fn restore(
+        &self,
+        key: &str,
+        revision: i32,
+        author: i32,
+    ) -> Result<Option<Vec<ResourceValue>>, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            if lock_resource(conn, key).optional()?.is_none() {
+                return Ok(None);
+            }
+            let Some(revision) = find(conn, key, revision)? else {
+                return Ok(None);
+            };
+            let Some(value) = revision.value else {
+                let lang = Lang::new(&revision.lang).unwrap_or_else(Lang::base);
+                remove_value(conn, key, &lang, author)?;
+                return Ok(Some(load_values(conn, key)?));
+            };
+            let value = ResourceValue {
+                key: revision.key,
+                lang: revision.lang,
+                value,
+            };
+            Ok(Some(upsert_values(conn, key, &[value], Some(author))?))
+        })
     }
 
-    fn delete(&self, key: &str) -> Result<(), Error> {
+    fn delete(&self, key: &str, author: i32) -> Result<Option<Resource>, Error> {
         let mut conn = self.get()?;
-        diesel::delete(resources::dsl::resources.filter(resources::dsl::key.eq(key)))
-            .execute(&mut conn)?;
-        Ok(())
+        Ok(diesel::update(
+            resources::dsl::resources
+                .filter(resources::key.eq(key))
+                .filter(resources::deleted_at.is_null()),
+        )
+        .set((
+            resources::deleted_at.eq(chrono::Utc::now().naive_utc()),
+            resources::deleted_by.eq(author),
+        ))
+        .get_result::<Resource>(&mut conn)
+        .optional()?)
     }
 
-    fn delete_lang(&self, key: &str, lang: &Country) -> Result<(), Error> {
+    fn get_trash(&self, query_config: &QueryConfig) -> Result<Vec<Resource>, Error> {
         let mut conn = self.get()?;
-        match lang {
-            Country::UnitedKingdom => return Err(Error::Validation(ValidationError::Country)),
-            Country::Poland => diesel::update(resources::dsl::resources)
-                .filter(resources::dsl::key.eq(key))
-                .set(resources::dsl::pl.eq::<Option<String>>(None))
+        Ok(query_config
+            .get_query()?
+            .filter(resources::deleted_at.is_not_null())
+            .load::<Resource>(&mut conn)?)
+    }
+
+    fn restore_from_trash(&self, key: &str) -> Result<Option<Resource>, Error> {
+        let mut conn = self.get()?;
+        Ok(diesel::update(
+            resources::dsl::resources
+                .filter(resources::key.eq(key))
+                .filter(resources::deleted_at.is_not_null()),
+        )
+        .set((
+            resources::deleted_at.eq::<Option<chrono::NaiveDateTime>>(None),
+            resources::deleted_by.eq::<Option<i32>>(None),
+        ))
+        .get_result::<Resource>(&mut conn)
+        .optional()?)
+    }
+
+    fn purge(&self, key: &str) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        Ok(diesel::delete(
+            resources::dsl::resources
+                .filter(resources::key.eq(key))
+                .filter(resources::deleted_at.is_not_null()),
+        )
+        .execute(&mut conn)?)
+    }
+
+    fn purge_deleted(&self, older_than: chrono::NaiveDateTime) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        Ok(
+            diesel::delete(resources::dsl::resources.filter(resources::deleted_at.lt(older_than)))
                 .execute(&mut conn)?,
+        )
+    }
+
+    fn delete_lang(&self, key: &str, lang: &Lang, author: i32) -> Result<(), Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            lock_resource(conn, key)?;
+            remove_value(conn, key, lang, author)
+        })
+    }
+
+    fn import(
+        &self,
+        values: &[ResourceValue],
+        prefix: Option<&str>,
+        langs: &[Lang],
+        apply: bool,
+        author: i32,
+    ) -> Result<ImportDiff, Error> {
+        let values = values
+            .iter()
+            .filter(|v| langs.iter().any(|l| v.lang == **l))
+            .collect::<Vec<_>>();
+        if let Some(value) = values
+            .iter()
+            .find(|v| prefix.is_some_and(|p| !v.key.starts_with(p)))
+        {
+            return Err(Error::Status(
+                Status::BadRequest.code,
+                format!("Key {} is outside of the imported prefix", value.key),
+            ));
+        }
+        let keys = values
+            .iter()
+            .map(|v| v.key.as_str())
+            .collect::<BTreeSet<_>>();
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let current = load_values_in(conn, prefix, langs)?;
+            let existing = resources::dsl::resources
+                .filter(resources::key.eq_any(&keys))
+                .select((resources::key, resources::deleted_at.is_not_null()))
+                .load::<(String, bool)>(conn)?;
+            if let Some((key, _)) = existing.iter().find(|(_, trashed)| *trashed) {
+                return Err(Error::Status(
+                    Status::Conflict.code,
+                    format!("Resource {} is in the trash", key),
+                ));
+            }
+            let existing = existing.into_iter().map(|(k, _)| k).collect::<Vec<_>>();
+            // Keys must keep their base value and new ones need one to be created
+            let base_in_scope = langs.iter().any(|l| l.is_base());
+            for key in &keys {
+                let has_base = values.iter().any(|v| v.key == *key && v.lang == BASE_LANG);
+                let is_new = !existing.iter().any(|k| k == key);
+                if !has_base && (base_in_scope || is_new) {
+                    return Err(Error::Validation(ValidationError::ResourceData(
+                        ResourceDataValidationError::ValueMissing,
+                    )));
+                }
+                if key.is_empty() || key.chars().count() > KEY_MAX_LENGTH {
+                    return Err(Error::Validation(ValidationError::ResourceData(
+                        ResourceDataValidationError::KeyMissing,
+                    )));
+                }
+            }
+
+            let mut diff = ImportDiff::default();
+            for value in &values {
+                let value_ref = ValueRef {
+                    key: value.key.clone(),
+                    lang: value.lang.clone(),
+                };
+                match current
+                    .iter()
+                    .find(|c| c.key == value.key && c.lang == value.lang)
+                {
+                    None => diff.added.push(value_ref),
+                    Some(c) if c.value != value.value => diff.changed.push(value_ref),
+                    Some(_) => {}
+                }
+            }
+            diff.removed = current
+                .iter()
+                .filter(|c| !values.iter().any(|v| v.key == c.key && v.lang == c.lang))
+                .map(|c| ValueRef {
+                    key: c.key.clone(),
+                    lang: c.lang.clone(),
+                })
+                .collect();
+            if !apply {
+                return Ok(diff);
+            }
+
+            let new_keys = keys
+                .iter()
+                .filter(|k| !existing.iter().any(|e| e == *k))
+                .map(|k| Resource {
+                    key: k.to_string(),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(resources::dsl::resources)
+                .values(&new_keys)
+                .execute(conn)?;
+            for key in &keys {
+                let key_values = values
+                    .iter()
+                    .filter(|v| v.key == *key)
+                    .map(|v| (*v).clone())
+                    .collect::<Vec<_>>();
+                upsert_values(conn, key, &key_values, Some(author))?;
+            }
+            // Base values are only removed along with the whole key, which goes to the trash
+            let removed_keys = diff
+                .removed
+                .iter()
+                .filter(|r| r.lang == BASE_LANG)
+                .map(|r| r.key.as_str())
+                .collect::<Vec<_>>();
+            diesel::update(
+                resources::dsl::resources
+                    .filter(resources::key.eq_any(&removed_keys))
+                    .filter(resources::deleted_at.is_null()),
+            )
+            .set((
+                resources::deleted_at.eq(chrono::Utc::now().naive_utc()),
+                resources::deleted_by.eq(author),
+            ))
+            .execute(conn)?;
+            for removed in diff
+                .removed
+                .iter()
+                .filter(|r| !removed_keys.contains(&r.key.as_str()))
+            {
+                let lang = Lang::new(&removed.lang).unwrap_or_else(Lang::base);
+                remove_value(conn, &removed.key, &lang, author)?;
+            }
+            diff.applied = true;
+            Ok(diff)
+        })
+    }
+}
+
+fn active_keys() -> ActiveKeys {
+    resources::table
+        .filter(resources::deleted_at.is_null())
+        .select(resources::key)
+}
+
+/// Locks the resource for the rest of the transaction, resources in the trash are not found.
+fn lock_resource(conn: &mut PgConnection, key: &str) -> QueryResult<Resource> {
+    resources::dsl::resources
+        .filter(resources::key.eq(key))
+        .filter(resources::deleted_at.is_null())
+        .for_update()
+        .first::<Resource>(conn)
+}
+
+fn load_values(conn: &mut PgConnection, key: &str) -> QueryResult<Vec<ResourceValue>> {
+    resource_values::dsl::resource_values
+        .filter(resource_values::key.eq(key))
+        .order(resource_values::lang.asc())
+        .load::<ResourceValue>(conn)
+}
+
+fn load_values_in(
+    conn: &mut PgConnection,
+    prefix: Option<&str>,
+    langs: &[Lang],
+) -> QueryResult<Vec<ResourceValue>> {
+    let mut query = resource_values::dsl::resource_values
+        .filter(resource_values::lang.eq_any(langs.iter().map(|l| l.to_string())))
+        .filter(resource_values::key.eq_any(active_keys()))
+        .into_boxed();
+    if let Some(prefix) = prefix {
+        query = query.filter(resource_values::key.like(prefix_pattern(prefix)));
+    }
+    query
+        .order((resource_values::key.asc(), resource_values::lang.asc()))
+        .load::<ResourceValue>(conn)
+}
+
+/// `LIKE` pattern matching keys that start with the prefix.
+fn prefix_pattern(prefix: &str) -> String {
+    format!(
+        "{}%",
+        prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    )
+}
+
+fn remove_value(conn: &mut PgConnection, key: &str, lang: &Lang, author: i32) -> Result<(), Error> {
+    if lang.is_base() {
+        return Err(Error::Validation(ValidationError::Country));
+    }
+    let removed = diesel::delete(
+        resource_values::dsl::resource_values
+            .filter(resource_values::key.eq(key))
+            .filter(resource_values::lang.eq(lang.to_string())),
+    )
+    .execute(conn)?;
+    diesel::delete(
+        resource_drafts::dsl::resource_drafts
+            .filter(resource_drafts::key.eq(key))
+            .filter(resource_drafts::lang.eq(lang.to_string())),
+    )
+    .execute(conn)?;
+    if removed > 0 {
+        record(
+            conn,
+            &[ResourceRevision {
+                key: key.to_string(),
+                lang: lang.to_string(),
+                author: Some(author),
+                ..Default::default()
+            }],
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the values, dropping the drafts they supersede, and records a revision for each one that changed.
+fn upsert_values(
+    conn: &mut PgConnection,
+    key: &str,
+    values: &[ResourceValue],
+    author: Option<i32>,
+) -> Result<Vec<ResourceValue>, Error> {
+    check_values(conn, key, values)?;
+    let previous = load_values(conn, key)?;
+    let revisions = values
+        .iter()
+        .filter(|v| {
+            !previous
+                .iter()
+                .any(|p| p.lang == v.lang && p.value == v.value)
+        })
+        .map(|v| ResourceRevision {
+            key: key.to_string(),
+            lang: v.lang.clone(),
+            value: Some(v.value.clone()),
+            author,
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    diesel::insert_into(resource_values::dsl::resource_values)
+        .values(values)
+        .on_conflict((resource_values::key, resource_values::lang))
+        .do_update()
+        .set(resource_values::value.eq(excluded(resource_values::value)))
+        .execute(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                _,
+            ) => Error::Status(
+                Status::BadRequest.code,
+                "Language is not registered".to_string(),
+            ),
+            e => e.into(),
+        })?;
+    // Written values supersede pending drafts, which would overwrite them once published
+    diesel::delete(
+        resource_drafts::dsl::resource_drafts
+            .filter(resource_drafts::key.eq(key))
+            .filter(resource_drafts::lang.eq_any(values.iter().map(|v| &v.lang))),
+    )
+    .execute(conn)?;
+    if !revisions.is_empty() {
+        record(conn, &revisions)?;
+    }
+    Ok(load_values(conn, key)?)
+}
+
+/// Rejects values longer than the maximum length set for the key, counted in characters,
+/// and values that are not valid messages when the key has the `message` content type.
+pub fn check_values(
+    conn: &mut PgConnection,
+    key: &str,
+    values: &[ResourceValue],
+) -> Result<(), Error> {
+    let Some((max_length, content_type)) = resources::dsl::resources
+        .filter(resources::key.eq(key))
+        .select((resources::max_length, resources::content_type))
+        .first::<(Option<i32>, ResourceContentType)>(conn)
+        .optional()?
+    else {
+        return Ok(());
+    };
+    if let Some(max_length) = max_length {
+        if let Some(value) = values
+            .iter()
+            .find(|v| v.value.chars().count() > max_length as usize)
+        {
+            return Err(Error::Status(
+                Status::BadRequest.code,
+                format!(
+                    "Value of {} in {} is longer than {} characters",
+                    key, value.lang, max_length
+                ),
+            ));
+        }
+    }
+    if content_type == ResourceContentType::Message {
+        check_messages(
+            key,
+            values.iter().map(|v| (v.lang.as_str(), v.value.as_str())),
+        )?;
+    }
+    Ok(())
+}
+
+fn check_messages<'v>(
+    key: &str,
+    values: impl IntoIterator<Item = (&'v str, &'v str)>,
+) -> Result<(), Error> {
+    for (lang, value) in values {
+        Message::parse(value).map_err(|e| {
+            Error::Status(
+                Status::BadRequest.code,
+                format!("Invalid message of {} in {}: {}", key, lang, e),
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Replaces published values with the drafts of the same language.
+fn overlay_drafts(values: &mut Vec<ResourceValue>, drafts: Vec<ResourceDraft>) {
+    for draft in drafts {
+        let value = ResourceValue {
+            key: draft.key,
+            lang: draft.lang,
+            value: draft.value,
+        };
+        match values.iter_mut().find(|v| v.lang == value.lang) {
+            Some(v) => *v = value,
+            None => values.push(value),
+        }
+    }
+    values.sort_by(|a, b| a.lang.cmp(&b.lang));
+}
+
+/// Replaces values with the variants of the same key and language.
+fn overlay_variants(values: &mut Vec<ResourceValue>, variants: &[ResourceVariant]) {
+    for variant in variants {
+        let value = ResourceValue {
+            key: variant.key.clone(),
+            lang: variant.lang.clone(),
+            value: variant.value.clone(),
+        };
+        match values
+            .iter_mut()
+            .find(|v| v.key == value.key && v.lang == value.lang)
+        {
+            Some(v) => *v = value,
+            None => values.push(value),
+        }
+    }
+}
+
+/// Stores the values as drafts and returns the resource as it would look once they are published.
+fn save_drafts(
+    conn: &mut PgConnection,
+    key: &str,
+    values: &[ResourceValue],
+    author: i32,
+) -> Result<Vec<ResourceValue>, Error> {
+    check_values(conn, key, values)?;
+    let drafts = values
+        .iter()
+        .map(|v| ResourceDraft {
+            key: key.to_string(),
+            lang: v.lang.clone(),
+            value: v.value.clone(),
+            author: Some(author),
+            publish_at: None,
+            updated_at: None,
+        })
+        .collect::<Vec<_>>();
+    diesel::insert_into(resource_drafts::dsl::resource_drafts)
+        .values(&drafts)
+        .on_conflict((resource_drafts::key, resource_drafts::lang))
+        .do_update()
+        .set((
+            resource_drafts::value.eq(excluded(resource_drafts::value)),
+            resource_drafts::author.eq(excluded(resource_drafts::author)),
+            // Changed text has to be scheduled again, it must not go out at the old time unreviewed
+            resource_drafts::publish_at.eq::<Option<chrono::NaiveDateTime>>(None),
+            resource_drafts::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                _,
+            ) => Error::Status(
+                Status::BadRequest.code,
+                "Language is not registered".to_string(),
+            ),
+            e => e.into(),
+        })?;
+    let mut values = load_values(conn, key)?;
+    overlay_drafts(
+        &mut values,
+        resource_drafts::dsl::resource_drafts
+            .filter(resource_drafts::key.eq(key))
+            .load::<ResourceDraft>(conn)?,
+    );
+    Ok(values)
+}
+
+/// Moves the drafts into the published values, recording their authors in the revisions.
+fn publish_drafts(conn: &mut PgConnection, drafts: &[ResourceDraft]) -> Result<(), Error> {
+    for draft in drafts {
+        let value = ResourceValue {
+            key: draft.key.clone(),
+            lang: draft.lang.clone(),
+            value: draft.value.clone(),
         };
-        Ok(())
+        upsert_values(conn, &draft.key, &[value], draft.author)?;
     }
+    Ok(())
 }
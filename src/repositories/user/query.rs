@@ -1,10 +1,11 @@
 use crate::{impl_query_config, schema::users};
+use diesel::pg::Pg;
 
 impl_query_config!(
     users::dsl::users,
-    users::table,
     users::BoxedQuery<'static, Pg>,
     UsersQuery,
+    users::id,
     [
         (users::id, "id"),
         (users::name, "name"),
@@ -13,5 +14,7 @@ impl_query_config!(
         (users::confirmed, "confirmed"),
         (users::created_at, "created_at"),
         (users::deleted_at, "deleted_at"),
+        (users::suspended_until, "suspended_until"),
+        (users::last_login_at, "last_login_at"),
     ]
 );
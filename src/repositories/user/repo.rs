@@ -1,16 +1,52 @@
 use super::query::UsersQuery;
-use crate::{models::user::User, repositories::query_config::QueryConfig, schema::users, PgPool};
-use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use crate::{
+    models::{
+        registration::{Registration, RegistrationStatus},
+        user::User,
+        user_bulk::{BulkUserAction, BulkUserResult, BulkUserStatus},
+        user_name::UserName,
+    },
+    repositories::{
+        invitation::repo::redeem, query_config::QueryConfig, registration::repo::resolve_pending,
+    },
+    schema::{user_registrations, users},
+    PgPool,
+};
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl,
+};
 use petompp_web_models::error::{Error, UserError};
 use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+use std::collections::{HashMap, HashSet};
+
+pub enum BulkSelection {
+    Ids(Vec<i32>),
+    Query(QueryConfig),
+}
 
 pub trait UserRepo: Send + Sync {
-    fn create(&self, user: &User) -> Result<User, Error>;
+    fn create(&self, user: &User, registration: &Registration) -> Result<User, Error>;
     fn get_by_name(&self, normalized_name: String) -> Result<Option<User>, Error>;
     fn get_by_id(&self, id: i32) -> Result<Option<User>, Error>;
     fn get_all(&self, query_config: &QueryConfig) -> Result<Vec<Vec<User>>, Error>;
     fn activate(&self, id: i32) -> Result<Option<User>, Error>;
     fn delete(&self, id: i32) -> Result<Option<User>, Error>;
+    fn rename(&self, id: i32, name: &str) -> Result<Option<User>, Error>;
+    fn suspend(
+        &self,
+        id: i32,
+        until: Option<chrono::NaiveDateTime>,
+        reason: Option<String>,
+    ) -> Result<Option<User>, Error>;
+    fn lift_expired_suspensions(&self) -> Result<usize, Error>;
+    fn purge_deleted(&self, older_than: chrono::NaiveDateTime) -> Result<usize, Error>;
+    fn normalize_names(&self) -> Result<usize, Error>;
+    fn bulk(
+        &self,
+        selection: &BulkSelection,
+        action: BulkUserAction,
+        dry_run: bool,
+    ) -> Result<Vec<BulkUserResult>, Error>;
 }
 
 #[async_trait]
@@ -27,13 +63,41 @@ impl<'r> FromRequest<'r> for &'r dyn UserRepo {
 }
 
 impl UserRepo for PgPool {
-    fn create(&self, user: &User) -> Result<User, Error> {
+    fn create(&self, user: &User, registration: &Registration) -> Result<User, Error> {
         let mut conn = self.get()?;
-        let user = diesel::insert_into(users::dsl::users)
-            .values(user)
-            .get_result::<User>(&mut conn)
-            .map_err(|e| unique_vol_as_user_exists(e, &*user.name))?;
-        Ok(user)
+        conn.transaction(|conn| {
+            let mut user = user.clone();
+            if let Some(code) = &registration.invitation_code {
+                let invitation = redeem(conn, code)?.ok_or_else(|| {
+                    Error::Status(
+                        Status::BadRequest.code,
+                        "Invalid or expired invitation code".to_string(),
+                    )
+                })?;
+                user.role = invitation.role.unwrap_or(user.role);
+                user.confirmed |= invitation.auto_confirm;
+            }
+            if skeleton_taken(conn, user.name_skeleton.as_deref(), None)? {
+                return Err(Error::User(UserError::NameTaken(user.name.0.clone())));
+            }
+            let user = diesel::insert_into(users::dsl::users)
+                .values(&user)
+                .get_result::<User>(conn)
+                .map_err(|e| unique_vol_as_user_exists(e, &*user.name))?;
+            let registration = Registration {
+                user_id: user.id,
+                name: user.name.0.clone(),
+                status: match user.confirmed {
+                    true => RegistrationStatus::Approved,
+                    false => RegistrationStatus::Pending,
+                },
+                ..registration.clone()
+            };
+            diesel::insert_into(user_registrations::dsl::user_registrations)
+                .values(&registration)
+                .execute(conn)?;
+            Ok(user)
+        })
     }
 
     fn get_by_name(&self, normalized_name: String) -> Result<Option<User>, Error> {
@@ -60,20 +124,220 @@ impl UserRepo for PgPool {
     }
 
     fn activate(&self, id: i32) -> Result<Option<User>, Error> {
+        let mut conn = self.get()?;
+        Ok(conn.transaction(|conn| {
+            resolve_pending(conn, &[id], RegistrationStatus::Approved, None, None)?;
+            diesel::update(users::dsl::users.filter(users::id.eq(id)))
+                .set(users::confirmed.eq(true))
+                .get_result::<User>(conn)
+                .optional()
+        })?)
+    }
+
+    fn delete(&self, id: i32) -> Result<Option<User>, Error> {
         let mut conn = self.get()?;
         Ok(diesel::update(users::dsl::users.filter(users::id.eq(id)))
-            .set(users::confirmed.eq(true))
+            .set(users::deleted_at.eq(chrono::Utc::now().naive_utc()))
             .get_result::<User>(&mut conn)
             .optional()?)
     }
 
-    fn delete(&self, id: i32) -> Result<Option<User>, Error> {
+    fn rename(&self, id: i32, name: &str) -> Result<Option<User>, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let skeleton = UserName::skeleton(name);
+            if skeleton_taken(conn, Some(&skeleton), Some(id))? {
+                return Err(Error::User(UserError::NameTaken(name.to_string())));
+            }
+            diesel::update(users::dsl::users.filter(users::id.eq(id)))
+                .set((
+                    users::name.eq(name),
+                    users::normalized_name.eq(UserName::normalize(name)),
+                    users::name_skeleton.eq(skeleton),
+                    users::name_conflict.eq(false),
+                ))
+                .get_result::<User>(conn)
+                .optional()
+                .map_err(|e| unique_vol_as_user_exists(e, name))
+        })
+    }
+
+    fn suspend(
+        &self,
+        id: i32,
+        until: Option<chrono::NaiveDateTime>,
+        reason: Option<String>,
+    ) -> Result<Option<User>, Error> {
         let mut conn = self.get()?;
         Ok(diesel::update(users::dsl::users.filter(users::id.eq(id)))
-            .set(users::deleted_at.eq(chrono::Utc::now().naive_utc()))
+            .set((
+                users::suspended_until.eq(until),
+                users::suspension_reason.eq(reason),
+            ))
             .get_result::<User>(&mut conn)
             .optional()?)
     }
+
+    fn lift_expired_suspensions(&self) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        Ok(diesel::update(
+            users::dsl::users.filter(users::suspended_until.le(chrono::Utc::now().naive_utc())),
+        )
+        .set((
+            users::suspended_until.eq::<Option<chrono::NaiveDateTime>>(None),
+            users::suspension_reason.eq::<Option<String>>(None),
+        ))
+        .execute(&mut conn)?)
+    }
+
+    fn purge_deleted(&self, older_than: chrono::NaiveDateTime) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let ids = users::dsl::users
+                .filter(users::deleted_at.lt(older_than))
+                .select(users::id)
+                .load::<i32>(conn)?;
+            // Registrations hold the signup IP and user agent, so they go with the user
+            diesel::delete(
+                user_registrations::dsl::user_registrations
+                    .filter(user_registrations::user_id.eq_any(&ids)),
+            )
+            .execute(conn)?;
+            Ok(diesel::delete(users::dsl::users.filter(users::id.eq_any(&ids))).execute(conn)?)
+        })
+    }
+
+    /// Fills in the names and skeletons of users created before names were normalized.
+    ///
+    /// Names colliding with another one are marked and only get their skeleton, so they are not retried.
+    fn normalize_names(&self) -> Result<usize, Error> {
+        let mut conn = self.get()?;
+        let users = users::dsl::users
+            .filter(users::name_skeleton.is_null())
+            .select((users::id, users::name, users::name_conflict))
+            .load::<(i32, String, bool)>(&mut conn)?;
+        let mut normalized = 0;
+        for (id, name, conflict) in users {
+            let skeleton = UserName::skeleton(&name);
+            if !conflict {
+                let result = diesel::update(users::dsl::users.filter(users::id.eq(id)))
+                    .set((
+                        users::normalized_name.eq(UserName::normalize(&name)),
+                        users::name_skeleton.eq(&skeleton),
+                    ))
+                    .execute(&mut conn);
+                match result {
+                    Ok(_) => {
+                        normalized += 1;
+                        continue;
+                    }
+                    Err(diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    )) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            diesel::update(users::dsl::users.filter(users::id.eq(id)))
+                .set((
+                    users::name_conflict.eq(true),
+                    users::name_skeleton.eq(&skeleton),
+                ))
+                .execute(&mut conn)?;
+        }
+        Ok(normalized)
+    }
+
+    fn bulk(
+        &self,
+        selection: &BulkSelection,
+        action: BulkUserAction,
+        dry_run: bool,
+    ) -> Result<Vec<BulkUserResult>, Error> {
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let mut ids = match selection {
+                BulkSelection::Ids(ids) => ids.clone(),
+                BulkSelection::Query(query_config) => query_config
+                    .get_query()?
+                    .select(users::id)
+                    .load::<i32>(conn)?,
+            };
+            let mut seen = HashSet::new();
+            ids.retain(|id| seen.insert(*id));
+            let mut users = users::dsl::users
+                .filter(users::id.eq_any(&ids))
+                .for_update()
+                .load::<User>(conn)?
+                .into_iter()
+                .map(|u| (u.id.unwrap(), u))
+                .collect::<HashMap<_, _>>();
+            let mut changed = Vec::new();
+            let results = ids
+                .into_iter()
+                .map(|id| match users.remove(&id) {
+                    Some(mut user) => {
+                        let status = match action.apply(&mut user) {
+                            true => {
+                                changed.push(id);
+                                BulkUserStatus::Changed
+                            }
+                            false => BulkUserStatus::Unchanged,
+                        };
+                        BulkUserResult {
+                            id,
+                            status,
+                            user: Some(user.into()),
+                        }
+                    }
+                    None => BulkUserResult {
+                        id,
+                        status: BulkUserStatus::NotFound,
+                        user: None,
+                    },
+                })
+                .collect::<Vec<_>>();
+            if dry_run || changed.is_empty() {
+                return Ok(results);
+            }
+            let target = users::dsl::users.filter(users::id.eq_any(&changed));
+            match action {
+                BulkUserAction::Activate => {
+                    resolve_pending(conn, &changed, RegistrationStatus::Approved, None, None)?;
+                    diesel::update(target)
+                        .set(users::confirmed.eq(true))
+                        .execute(conn)?
+                }
+                BulkUserAction::Delete => diesel::update(target)
+                    .set(users::deleted_at.eq(chrono::Utc::now().naive_utc()))
+                    .execute(conn)?,
+                BulkUserAction::Restore => diesel::update(target)
+                    .set(users::deleted_at.eq::<Option<chrono::NaiveDateTime>>(None))
+                    .execute(conn)?,
+                BulkUserAction::SetRole(role) => diesel::update(target)
+                    .set(users::role.eq(role))
+                    .execute(conn)?,
+            };
+            Ok(results)
+        })
+    }
+}
+
+/// Checks whether another user has a name that looks like the one with the given skeleton.
+fn skeleton_taken(
+    conn: &mut PgConnection,
+    skeleton: Option<&str>,
+    except: Option<i32>,
+) -> Result<bool, Error> {
+    let Some(skeleton) = skeleton else {
+        return Ok(false);
+    };
+    let count = users::dsl::users
+        .filter(users::name_skeleton.eq(skeleton))
+        .filter(users::id.ne(except.unwrap_or(-1)))
+        .count()
+        .get_result::<i64>(conn)?;
+    Ok(count > 0)
 }
 
 fn unique_vol_as_user_exists(e: diesel::result::Error, name: impl Into<String>) -> Error {
@@ -0,0 +1,79 @@
+use crate::{
+    models::user_preferences::{UserPreference, UserPreferences, UserPreferencesPatch},
+    schema::user_preferences,
+    PgPool,
+};
+use diesel::{upsert::excluded, Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
+use petompp_web_models::error::Error;
+use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
+
+pub trait UserPreferencesRepo: Send + Sync {
+    fn get(&self, user_id: i32) -> Result<UserPreferences, Error>;
+    fn update(&self, user_id: i32, patch: &UserPreferencesPatch) -> Result<UserPreferences, Error>;
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for &'r dyn UserPreferencesRepo {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, (Status, Self::Error), ()> {
+        let pool = request
+            .guard::<&rocket::State<&dyn UserPreferencesRepo>>()
+            .await
+            .unwrap();
+        Outcome::Success(*pool.inner())
+    }
+}
+
+impl UserPreferencesRepo for PgPool {
+    fn get(&self, user_id: i32) -> Result<UserPreferences, Error> {
+        let mut conn = self.get()?;
+        Ok(user_preferences::dsl::user_preferences
+            .filter(user_preferences::user_id.eq(user_id))
+            .load::<UserPreference>(&mut conn)?
+            .into())
+    }
+
+    fn update(&self, user_id: i32, patch: &UserPreferencesPatch) -> Result<UserPreferences, Error> {
+        let changes = patch
+            .changes()
+            .map_err(|e| Error::Status(Status::BadRequest.code, e.to_string()))?;
+        let mut conn = self.get()?;
+        conn.transaction(|conn| {
+            let now = chrono::Utc::now().naive_utc();
+            for (key, value) in changes {
+                match value {
+                    Some(value) => {
+                        diesel::insert_into(user_preferences::dsl::user_preferences)
+                            .values(&UserPreference {
+                                user_id,
+                                key,
+                                value,
+                                updated_at: now,
+                            })
+                            .on_conflict((user_preferences::user_id, user_preferences::key))
+                            .do_update()
+                            .set((
+                                user_preferences::value.eq(excluded(user_preferences::value)),
+                                user_preferences::updated_at
+                                    .eq(excluded(user_preferences::updated_at)),
+                            ))
+                            .execute(conn)?;
+                    }
+                    None => {
+                        diesel::delete(
+                            user_preferences::dsl::user_preferences
+                                .filter(user_preferences::user_id.eq(user_id))
+                                .filter(user_preferences::key.eq(key)),
+                        )
+                        .execute(conn)?;
+                    }
+                }
+            }
+            Ok(user_preferences::dsl::user_preferences
+                .filter(user_preferences::user_id.eq(user_id))
+                .load::<UserPreference>(conn)?
+                .into())
+        })
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{models::user_settings::UserSettings, schema::user_settings, PgPool};
+use crate::{
+    models::user_settings::{RegistrationSettings, UserSettings},
+    schema::user_settings,
+    PgPool,
+};
 use diesel::RunQueryDsl;
 use petompp_web_models::error::Error;
 use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest, Request};
@@ -6,6 +10,7 @@ use rocket::{async_trait, http::Status, outcome::Outcome, request::FromRequest,
 pub trait UserSettingsRepo: Send + Sync {
     fn get(&self) -> Result<UserSettings, Error>;
     fn update(&self, settings: &UserSettings) -> Result<UserSettings, Error>;
+    fn update_registration(&self, settings: &RegistrationSettings) -> Result<UserSettings, Error>;
 }
 
 #[async_trait]
@@ -33,4 +38,11 @@ impl UserSettingsRepo for PgPool {
             .set(settings)
             .get_result::<UserSettings>(&mut conn)?)
     }
+
+    fn update_registration(&self, settings: &RegistrationSettings) -> Result<UserSettings, Error> {
+        let mut conn = self.get()?;
+        Ok(diesel::update(user_settings::dsl::user_settings)
+            .set(settings)
+            .get_result::<UserSettings>(&mut conn)?)
+    }
 }
@@ -1,11 +1,172 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tsvector", schema = "pg_catalog"))]
+    pub struct Tsvector;
+}
+
+diesel::table! {
+    invitation_codes (code) {
+        #[max_length = 64]
+        code -> Varchar,
+        max_uses -> Nullable<Int4>,
+        uses -> Int4,
+        role -> Nullable<Int4>,
+        auto_confirm -> Bool,
+        expires_at -> Nullable<Timestamp>,
+        created_by -> Nullable<Int4>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    languages (code) {
+        #[max_length = 16]
+        code -> Varchar,
+        #[max_length = 64]
+        name -> Varchar,
+        created_at -> Timestamp,
+        #[max_length = 16]
+        fallback -> Nullable<Varchar>,
+        #[max_length = 64]
+        search_config -> Varchar,
+    }
+}
+
+diesel::table! {
+    reserved_names (id) {
+        id -> Int4,
+        #[max_length = 255]
+        pattern -> Varchar,
+        kind -> Int4,
+        reason -> Nullable<Text>,
+        created_by -> Nullable<Int4>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    resource_drafts (key, lang) {
+        #[max_length = 64]
+        key -> Varchar,
+        #[max_length = 16]
+        lang -> Varchar,
+        value -> Text,
+        author -> Nullable<Int4>,
+        publish_at -> Nullable<Timestamp>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    resource_revisions (id) {
+        id -> Int4,
+        #[max_length = 64]
+        key -> Varchar,
+        #[max_length = 16]
+        lang -> Varchar,
+        value -> Nullable<Text>,
+        author -> Nullable<Int4>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Tsvector;
+
+    resource_search (key, lang) {
+        #[max_length = 64]
+        key -> Varchar,
+        #[max_length = 16]
+        lang -> Varchar,
+        document -> Tsvector,
+    }
+}
+
+diesel::table! {
+    resource_values (key, lang) {
+        #[max_length = 64]
+        key -> Varchar,
+        #[max_length = 16]
+        lang -> Varchar,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    resource_variants (id) {
+        id -> Int4,
+        #[max_length = 64]
+        key -> Varchar,
+        #[max_length = 16]
+        lang -> Varchar,
+        value -> Text,
+        valid_from -> Nullable<Timestamp>,
+        valid_until -> Nullable<Timestamp>,
+        author -> Nullable<Int4>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     resources (key) {
         #[max_length = 64]
         key -> Varchar,
-        en -> Text,
-        pl -> Nullable<Text>,
+        updated_at -> Timestamp,
+        description -> Nullable<Text>,
+        owner -> Nullable<Int4>,
+        content_type -> Int4,
+        max_length -> Nullable<Int4>,
+        deleted_at -> Nullable<Timestamp>,
+        deleted_by -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    user_logins (id) {
+        id -> Int4,
+        user_id -> Nullable<Int4>,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 45]
+        ip -> Nullable<Varchar>,
+        #[max_length = 512]
+        user_agent -> Nullable<Varchar>,
+        method -> Int4,
+        success -> Bool,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_preferences (user_id, key) {
+        user_id -> Int4,
+        #[max_length = 64]
+        key -> Varchar,
+        value -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_registrations (id) {
+        id -> Int4,
+        user_id -> Nullable<Int4>,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 45]
+        ip -> Nullable<Varchar>,
+        #[max_length = 512]
+        user_agent -> Nullable<Varchar>,
+        status -> Int4,
+        reason -> Nullable<Text>,
+        reviewed_by -> Nullable<Int4>,
+        created_at -> Timestamp,
+        reviewed_at -> Nullable<Timestamp>,
+        #[max_length = 64]
+        invitation_code -> Nullable<Varchar>,
     }
 }
 
@@ -23,6 +184,11 @@ diesel::table! {
         password_check_uppercase -> Bool,
         password_check_lowercase -> Bool,
         password_check_special_characters -> Bool,
+        registration_expiry_days -> Nullable<Int4>,
+        registration_mode -> Int4,
+        deleted_user_retention_days -> Nullable<Int4>,
+        #[max_length = 255]
+        allowed_scripts -> Nullable<Varchar>,
     }
 }
 
@@ -39,7 +205,45 @@ diesel::table! {
         confirmed -> Bool,
         created_at -> Timestamp,
         deleted_at -> Nullable<Timestamp>,
+        suspended_until -> Nullable<Timestamp>,
+        suspension_reason -> Nullable<Text>,
+        last_login_at -> Nullable<Timestamp>,
+        #[max_length = 255]
+        name_skeleton -> Nullable<Varchar>,
+        name_conflict -> Bool,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(resources, user_settings, users,);
+diesel::joinable!(invitation_codes -> users (created_by));
+diesel::joinable!(reserved_names -> users (created_by));
+diesel::joinable!(resource_drafts -> languages (lang));
+diesel::joinable!(resource_drafts -> resources (key));
+diesel::joinable!(resource_drafts -> users (author));
+diesel::joinable!(resource_revisions -> resources (key));
+diesel::joinable!(resource_revisions -> users (author));
+diesel::joinable!(resource_values -> languages (lang));
+diesel::joinable!(resource_values -> resources (key));
+diesel::joinable!(resource_variants -> languages (lang));
+diesel::joinable!(resource_variants -> resources (key));
+diesel::joinable!(resource_variants -> users (author));
+diesel::joinable!(resources -> users (owner));
+diesel::joinable!(user_logins -> users (user_id));
+diesel::joinable!(user_preferences -> users (user_id));
+diesel::joinable!(user_registrations -> invitation_codes (invitation_code));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    invitation_codes,
+    languages,
+    reserved_names,
+    resource_drafts,
+    resource_revisions,
+    resource_search,
+    resource_values,
+    resource_variants,
+    resources,
+    user_logins,
+    user_preferences,
+    user_registrations,
+    user_settings,
+    users,
+);
@@ -7,6 +7,9 @@ use petompp_web_models::{
 use rocket::{async_trait, futures::StreamExt, http::Status};
 use std::collections::HashMap;
 
+/// Blob metadata key holding the id of the user that uploaded the blob.
+pub const UPLOADED_BY_METADATA: &str = "uploaded_by";
+
 #[async_trait]
 pub trait AzureBlobService {
     async fn get(&self, container: String, filename: String) -> Result<BlobMetaData, Error>;
@@ -22,6 +25,8 @@ pub trait AzureBlobService {
         data: &[u8],
     ) -> Result<String, Error>;
     async fn delete(&self, container: String, pattern: String) -> Result<usize, Error>;
+    async fn get_uploaded_by(&self, user_id: i32) -> Result<Vec<(String, String)>, Error>;
+    async fn get_content(&self, container: String, filename: String) -> Result<Vec<u8>, Error>;
 }
 
 #[async_trait]
@@ -192,4 +197,45 @@ impl AzureBlobService for ClientBuilder {
             })
             .await?)
     }
+
+    async fn get_uploaded_by(&self, user_id: i32) -> Result<Vec<(String, String)>, Error> {
+        let uploader = user_id.to_string();
+        let mut containers = self
+            .clone()
+            .blob_service_client()
+            .list_containers()
+            .into_stream();
+        let mut result = Vec::new();
+        while let Some(resp) = containers.next().await {
+            for container in resp?.containers {
+                let mut stream = self
+                    .clone()
+                    .blob_service_client()
+                    .container_client(container.name.clone())
+                    .list_blobs()
+                    .include_metadata(true)
+                    .into_stream();
+                while let Some(resp) = stream.next().await {
+                    for blob in resp?.blobs.blobs() {
+                        let uploaded_by = blob
+                            .metadata
+                            .as_ref()
+                            .and_then(|m| m.get(UPLOADED_BY_METADATA));
+                        if uploaded_by == Some(&uploader) {
+                            result.push((container.name.clone(), blob.name.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn get_content(&self, container: String, filename: String) -> Result<Vec<u8>, Error> {
+        Ok(self
+            .clone()
+            .blob_client(container, filename)
+            .get_content()
+            .await?)
+    }
 }
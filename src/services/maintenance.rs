@@ -0,0 +1,126 @@
+use crate::{
+    repositories::{
+        registration::repo::RegistrationRepo, resources::repo::ResourcesRepo, user::repo::UserRepo,
+        user_settings::repo::UserSettingsRepo,
+    },
+    PgPool,
+};
+use petompp_web_models::error::Error;
+use rocket::{
+    async_trait,
+    fairing::{Fairing, Info, Kind},
+    http::Status,
+    tokio::{task, time},
+    Orbit, Rocket,
+};
+use std::time::Duration;
+
+/// Periodically runs cleanup jobs against the database.
+///
+/// The period in seconds is read from the `maintenance_interval` config value, zero falls back to the default,
+/// and the days deleted resources stay in the trash from `resource_trash_retention_days`.
+pub struct Maintenance;
+
+const DEFAULT_INTERVAL_S: u64 = 300;
+const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+const MAX_RETENTION_DAYS: i64 = 36_500;
+
+type Job<'a> = &'a dyn Fn(&PgPool) -> Result<(), Error>;
+
+#[async_trait]
+impl Fairing for Maintenance {
+    fn info(&self) -> Info {
+        Info {
+            name: "Maintenance",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let pool: &'static PgPool = rocket
+            .state::<&'static PgPool>()
+            .expect("PgPool must be managed");
+        let period = rocket
+            .figment()
+            .extract_inner::<u64>("maintenance_interval")
+            .ok()
+            .filter(|period| *period > 0)
+            .unwrap_or(DEFAULT_INTERVAL_S);
+        let trash_retention_days = rocket
+            .figment()
+            .extract_inner::<i64>("resource_trash_retention_days")
+            .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+        rocket::tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(period));
+            loop {
+                interval.tick().await;
+                if let Err(e) = task::spawn_blocking(move || run(pool, trash_retention_days)).await
+                {
+                    println!("Maintenance task failed: {:?}", e);
+                }
+            }
+        });
+    }
+}
+
+fn run(pool: &PgPool, trash_retention_days: i64) {
+    let jobs: [(&str, Job); 5] = [
+        ("expire registrations", &expire_registrations),
+        ("lift suspensions", &lift_suspensions),
+        ("purge deleted users", &purge_deleted_users),
+        ("publish scheduled resources", &publish_scheduled_resources),
+        ("purge resource trash", &|pool| {
+            purge_resource_trash(pool, trash_retention_days)
+        }),
+    ];
+    for (name, job) in jobs {
+        if let Err(e) = job(pool) {
+            println!("Maintenance job '{}' failed: {:?}", name, e);
+        }
+    }
+}
+
+fn expire_registrations(pool: &PgPool) -> Result<(), Error> {
+    let Some(days) = UserSettingsRepo::get(pool)?.registration_expiry_days() else {
+        return Ok(());
+    };
+    RegistrationRepo::expire_stale(pool, days_ago(days as i64)?)?;
+    Ok(())
+}
+
+fn lift_suspensions(pool: &PgPool) -> Result<(), Error> {
+    UserRepo::lift_expired_suspensions(pool)?;
+    Ok(())
+}
+
+fn purge_deleted_users(pool: &PgPool) -> Result<(), Error> {
+    let Some(days) = UserSettingsRepo::get(pool)?.deleted_user_retention_days() else {
+        return Ok(());
+    };
+    UserRepo::purge_deleted(pool, days_ago(days as i64)?)?;
+    Ok(())
+}
+
+fn publish_scheduled_resources(pool: &PgPool) -> Result<(), Error> {
+    ResourcesRepo::publish_scheduled(pool)?;
+    Ok(())
+}
+
+fn purge_resource_trash(pool: &PgPool, retention_days: i64) -> Result<(), Error> {
+    ResourcesRepo::purge_deleted(pool, days_ago(retention_days)?)?;
+    Ok(())
+}
+
+/// Start of the retention period, configured values outside of `0..=MAX_RETENTION_DAYS` are reported.
+fn days_ago(days: i64) -> Result<chrono::NaiveDateTime, Error> {
+    if !(0..=MAX_RETENTION_DAYS).contains(&days) {
+        return Err(Error::Status(
+            Status::InternalServerError.code,
+            format!(
+                "Retention of {} days is outside of 0-{}",
+                days, MAX_RETENTION_DAYS
+            ),
+        ));
+    }
+    Ok(chrono::Utc::now().naive_utc() - chrono::Duration::days(days))
+}
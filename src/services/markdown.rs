@@ -0,0 +1,94 @@
+use crate::models::resource_data::ResourceContentType;
+use pulldown_cmark::{html, Options, Parser};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Mutex, PoisonError},
+};
+
+const MAX_CACHED: usize = 512;
+
+/// Renders values to sanitized HTML according to their content type.
+///
+/// Output is cached by the hash of the content and its type, the oldest entries are dropped once the cache is full.
+#[derive(Default)]
+pub struct MarkdownRenderer {
+    cache: Mutex<RenderCache>,
+}
+
+#[derive(Default)]
+struct RenderCache {
+    entries: HashMap<[u8; 32], String>,
+    order: VecDeque<[u8; 32]>,
+}
+
+impl MarkdownRenderer {
+    pub fn render(&self, content: &str, content_type: ResourceContentType) -> String {
+        let hash: [u8; 32] = Sha256::new()
+            .chain_update([content_type as u8])
+            .chain_update(content.as_bytes())
+            .finalize()
+            .into();
+        if let Some(html) = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .get(&hash)
+        {
+            return html.clone();
+        }
+        let html = match content_type {
+            ResourceContentType::Markdown => render(content),
+            ResourceContentType::Html => sanitize(content),
+            ResourceContentType::Plain | ResourceContentType::Message => escape(content),
+        };
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if !cache.entries.contains_key(&hash) {
+            if cache.order.len() >= MAX_CACHED {
+                if let Some(oldest) = cache.order.pop_front() {
+                    cache.entries.remove(&oldest);
+                }
+            }
+            cache.order.push_back(hash);
+            cache.entries.insert(hash, html.clone());
+        }
+        html
+    }
+}
+
+/// Renders CommonMark with the GFM extensions and strips everything outside of the allowlist.
+///
+/// Only `http`, `https` and `mailto` links are kept and all of them get `rel="noopener noreferrer nofollow"`.
+fn render(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+    sanitize(&unsafe_html)
+}
+
+fn sanitize(html: &str) -> String {
+    ammonia::Builder::default()
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(html)
+        .to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
@@ -1,3 +1,7 @@
 pub mod azure_blob;
 pub mod azure_container;
 pub mod azure_test;
+pub mod maintenance;
+pub mod markdown;
+pub mod resource_transfer;
+pub mod user_export;
@@ -0,0 +1,413 @@
+use crate::models::{
+    lang::{Lang, BASE_LANG},
+    resource_data::ResourceValue,
+    resource_transfer::TransferFormat,
+};
+use petompp_web_models::error::Error;
+use quick_xml::{
+    events::{BytesText, Event},
+    Reader, Writer,
+};
+use rocket::{http::Status, serde::json::serde_json};
+use std::collections::BTreeMap;
+
+const XLIFF_NAMESPACE: &str = "urn:oasis:names:tc:xliff:document:2.0";
+
+/// Values read from an import file along with the languages the file covers.
+pub struct ImportedValues {
+    pub langs: Vec<Lang>,
+    pub values: Vec<ResourceValue>,
+}
+
+/// Writes the values in the given format.
+///
+/// JSON and CSV hold every language in `langs`, XLIFF and PO pair the base value
+/// with the only language in `langs`.
+pub fn export(
+    format: TransferFormat,
+    values: &[ResourceValue],
+    langs: &[Lang],
+) -> Result<String, Error> {
+    let mut keys = BTreeMap::<&str, BTreeMap<&str, &str>>::new();
+    for value in values {
+        keys.entry(&value.key)
+            .or_default()
+            .insert(&value.lang, &value.value);
+    }
+    match format {
+        TransferFormat::Json => serde_json::to_string_pretty(&keys).map_err(transfer_error),
+        TransferFormat::Csv => export_csv(&keys, langs),
+        TransferFormat::Xliff => export_xliff(&keys, target_lang(langs)?),
+        TransferFormat::Po => Ok(export_po(&keys, target_lang(langs)?)),
+    }
+}
+
+/// Reads the values of an import file.
+///
+/// XLIFF and PO files only provide values of their target language, which is `lang`
+/// or the one declared in the file.
+pub fn import(
+    format: TransferFormat,
+    content: &str,
+    lang: Option<&Lang>,
+) -> Result<ImportedValues, Error> {
+    let imported = match format {
+        TransferFormat::Json => import_json(content)?,
+        TransferFormat::Csv => import_csv(content)?,
+        TransferFormat::Xliff => import_xliff(content, lang)?,
+        TransferFormat::Po => import_po(content, lang)?,
+    };
+    if format.is_bilingual() && imported.langs.iter().any(|l| l.is_base()) {
+        return Err(invalid(
+            "Target language must not be the base language".to_string(),
+        ));
+    }
+    Ok(imported)
+}
+
+fn target_lang(langs: &[Lang]) -> Result<&Lang, Error> {
+    match langs {
+        [lang] if !lang.is_base() => Ok(lang),
+        _ => Err(invalid(
+            "Specify exactly one target language other than the base language".to_string(),
+        )),
+    }
+}
+
+fn parse_lang(code: &str) -> Result<Lang, Error> {
+    Lang::new(code).ok_or_else(|| invalid(format!("Invalid language code: {}", code)))
+}
+
+fn export_csv(
+    keys: &BTreeMap<&str, BTreeMap<&str, &str>>,
+    langs: &[Lang],
+) -> Result<String, Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(std::iter::once("key").chain(langs.iter().map(|l| l.as_str())))
+        .map_err(transfer_error)?;
+    for (key, values) in keys {
+        let row = langs
+            .iter()
+            .map(|l| values.get(l.as_str()).copied().unwrap_or_default());
+        writer
+            .write_record(std::iter::once(*key).chain(row))
+            .map_err(transfer_error)?;
+    }
+    let content = writer.into_inner().map_err(transfer_error)?;
+    String::from_utf8(content).map_err(transfer_error)
+}
+
+fn export_xliff(keys: &BTreeMap<&str, BTreeMap<&str, &str>>, lang: &Lang) -> Result<String, Error> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer
+        .create_element("xliff")
+        .with_attribute(("xmlns", XLIFF_NAMESPACE))
+        .with_attribute(("version", "2.0"))
+        .with_attribute(("srcLang", BASE_LANG))
+        .with_attribute(("trgLang", lang.as_str()))
+        .write_inner_content(|w| {
+            w.create_element("file")
+                .with_attribute(("id", "resources"))
+                .write_inner_content(|w| {
+                    for (key, values) in keys {
+                        w.create_element("unit")
+                            .with_attribute(("id", *key))
+                            .write_inner_content(|w| {
+                                w.create_element("segment").write_inner_content(|w| {
+                                    let source = values.get(BASE_LANG).copied().unwrap_or_default();
+                                    w.create_element("source")
+                                        .write_text_content(BytesText::new(source))?;
+                                    if let Some(target) = values.get(lang.as_str()) {
+                                        w.create_element("target")
+                                            .write_text_content(BytesText::new(target))?;
+                                    }
+                                    Ok::<_, quick_xml::Error>(())
+                                })?;
+                                Ok::<_, quick_xml::Error>(())
+                            })?;
+                    }
+                    Ok::<_, quick_xml::Error>(())
+                })?;
+            Ok::<_, quick_xml::Error>(())
+        })
+        .map_err(transfer_error)?;
+    let content = String::from_utf8(writer.into_inner()).map_err(transfer_error)?;
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}\n",
+        content
+    ))
+}
+
+fn export_po(keys: &BTreeMap<&str, BTreeMap<&str, &str>>, lang: &Lang) -> String {
+    let mut content = format!(
+        "msgid \"\"\nmsgstr \"\"\n\"Language: {}\\n\"\n\"MIME-Version: 1.0\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n",
+        lang
+    );
+    for (key, values) in keys {
+        content.push_str(&format!(
+            "\nmsgctxt \"{}\"\nmsgid \"{}\"\nmsgstr \"{}\"\n",
+            escape_po(key),
+            escape_po(values.get(BASE_LANG).copied().unwrap_or_default()),
+            escape_po(values.get(lang.as_str()).copied().unwrap_or_default()),
+        ));
+    }
+    content
+}
+
+fn import_json(content: &str) -> Result<ImportedValues, Error> {
+    let keys = serde_json::from_str::<BTreeMap<String, BTreeMap<String, String>>>(content)
+        .map_err(invalid)?;
+    let mut langs = Vec::new();
+    let mut values = Vec::new();
+    for (key, key_values) in keys {
+        for (lang, value) in key_values {
+            let lang = parse_lang(&lang)?;
+            values.push(ResourceValue::new(&key, &lang, value));
+            if !langs.contains(&lang) {
+                langs.push(lang);
+            }
+        }
+    }
+    Ok(ImportedValues { langs, values })
+}
+
+fn import_csv(content: &str) -> Result<ImportedValues, Error> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers().map_err(invalid)?.clone();
+    if headers.get(0) != Some("key") {
+        return Err(invalid("First column must be \"key\"".to_string()));
+    }
+    let langs = headers
+        .iter()
+        .skip(1)
+        .map(parse_lang)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut values = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(invalid)?;
+        let key = record.get(0).unwrap_or_default();
+        // Empty cells stand for missing values
+        for (lang, value) in langs.iter().zip(record.iter().skip(1)) {
+            if !value.is_empty() {
+                values.push(ResourceValue::new(key, lang, value.to_string()));
+            }
+        }
+    }
+    Ok(ImportedValues { langs, values })
+}
+
+fn import_xliff(content: &str, lang: Option<&Lang>) -> Result<ImportedValues, Error> {
+    let mut reader = Reader::from_str(content);
+    let mut lang = lang.cloned();
+    let mut unit = None;
+    let mut target = None;
+    let mut values = Vec::new();
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"xliff" => {
+                    if let (None, Some(attr)) =
+                        (&lang, e.try_get_attribute("trgLang").map_err(invalid)?)
+                    {
+                        lang = Some(parse_lang(&attr.unescape_value().map_err(invalid)?)?);
+                    }
+                }
+                b"unit" => {
+                    let id = e
+                        .try_get_attribute("id")
+                        .map_err(invalid)?
+                        .ok_or_else(|| invalid("Unit without an id".to_string()))?;
+                    unit = Some(id.unescape_value().map_err(invalid)?.into_owned());
+                }
+                b"target" => target = Some(String::new()),
+                _ => {}
+            },
+            Event::Text(e) => {
+                if let Some(target) = target.as_mut() {
+                    target.push_str(&e.unescape().map_err(invalid)?);
+                }
+            }
+            Event::CData(e) => {
+                if let Some(target) = target.as_mut() {
+                    target.push_str(&String::from_utf8_lossy(&e));
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"target" => {
+                    let (Some(key), Some(value)) = (&unit, target.take()) else {
+                        continue;
+                    };
+                    let lang = lang
+                        .as_ref()
+                        .ok_or_else(|| invalid("Target language is missing".to_string()))?;
+                    values.push(ResourceValue::new(key, lang, value));
+                }
+                b"unit" => unit = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let lang = lang.ok_or_else(|| invalid("Target language is missing".to_string()))?;
+    Ok(ImportedValues {
+        langs: vec![lang],
+        values,
+    })
+}
+
+#[derive(Default)]
+struct PoEntry {
+    msgctxt: Option<String>,
+    msgid: Option<String>,
+    msgstr: Option<String>,
+}
+
+fn import_po(content: &str, lang: Option<&Lang>) -> Result<ImportedValues, Error> {
+    let mut entries = Vec::new();
+    let mut entry = PoEntry::default();
+    let mut field = None;
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, rest) = match line.split_once(char::is_whitespace) {
+            Some((keyword, rest)) if !line.starts_with('"') => (keyword, rest.trim()),
+            _ => ("", line),
+        };
+        let text = unescape_po(rest)
+            .ok_or_else(|| invalid(format!("Invalid string on line {}", number + 1)))?;
+        // A new entry starts with its context or id once the previous one has a translation
+        if matches!(keyword, "msgctxt" | "msgid") && entry.msgstr.is_some() {
+            entries.push(std::mem::take(&mut entry));
+        }
+        let keyword = match keyword {
+            "" => {
+                field.ok_or_else(|| invalid(format!("Unexpected string on line {}", number + 1)))?
+            }
+            "msgctxt" | "msgid" | "msgstr" => keyword,
+            _ => {
+                return Err(invalid(format!(
+                    "Unsupported keyword {} on line {}",
+                    keyword,
+                    number + 1
+                )))
+            }
+        };
+        let target = match keyword {
+            "msgctxt" => &mut entry.msgctxt,
+            "msgid" => &mut entry.msgid,
+            _ => &mut entry.msgstr,
+        };
+        target.get_or_insert_with(String::new).push_str(&text);
+        field = Some(keyword);
+    }
+    entries.push(entry);
+
+    let mut lang = lang.cloned();
+    let mut values = Vec::new();
+    for entry in entries {
+        let (msgid, msgstr) = (
+            entry.msgid.unwrap_or_default(),
+            entry.msgstr.unwrap_or_default(),
+        );
+        let Some(key) = entry.msgctxt else {
+            if msgid.is_empty() {
+                // Header entry
+                if lang.is_none() {
+                    if let Some(code) = msgstr
+                        .lines()
+                        .find_map(|l| l.strip_prefix("Language:"))
+                        .filter(|c| !c.trim().is_empty())
+                    {
+                        lang = Some(parse_lang(code)?);
+                    }
+                }
+                continue;
+            }
+            return Err(invalid(format!("Entry \"{}\" has no msgctxt key", msgid)));
+        };
+        if msgstr.is_empty() {
+            continue;
+        }
+        values.push((key, msgstr));
+    }
+    let lang = lang.ok_or_else(|| invalid("Target language is missing".to_string()))?;
+    Ok(ImportedValues {
+        values: values
+            .into_iter()
+            .map(|(key, value)| ResourceValue::new(&key, &lang, value))
+            .collect(),
+        langs: vec![lang],
+    })
+}
+
+fn escape_po(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+fn unescape_po(value: &str) -> Option<String> {
+    let mut chars = value.strip_prefix('"')?.strip_suffix('"')?.chars();
+    let mut result = String::new();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => result.push('\n'),
+            't' => result.push('\t'),
+            'r' => result.push('\r'),
+            c @ ('\\' | '"') => result.push(c),
+            _ => return None,
+        }
+    }
+    Some(result)
+}
+
+fn invalid(e: impl std::fmt::Display) -> Error {
+    Error::Status(Status::BadRequest.code, e.to_string())
+}
+
+fn transfer_error(e: impl std::fmt::Display) -> Error {
+    Error::Status(Status::InternalServerError.code, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_whitespace_and_markup() {
+        let pl = Lang::new("pl").unwrap();
+        let values = [
+            ResourceValue::new(
+                "intro",
+                &Lang::base(),
+                "  Hello\n\n- **item**\n".to_string(),
+            ),
+            ResourceValue::new("intro", &pl, "\n  Cześć <b>&</b> \"x\"\n\n".to_string()),
+        ];
+        for format in [
+            TransferFormat::Json,
+            TransferFormat::Csv,
+            TransferFormat::Xliff,
+            TransferFormat::Po,
+        ] {
+            let content = export(format, &values, std::slice::from_ref(&pl)).unwrap();
+            let imported = import(format, &content, None).unwrap();
+            let value = imported
+                .values
+                .iter()
+                .find(|v| v.key == "intro" && v.lang == pl.as_str())
+                .unwrap();
+            assert_eq!(value.value, values[1].value, "{:?}", format);
+        }
+    }
+}
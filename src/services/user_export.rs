@@ -0,0 +1,28 @@
+use crate::models::user_export::UserExport;
+use petompp_web_models::error::Error;
+use rocket::{http::Status, serde::json::serde_json};
+use std::io::{Cursor, Write};
+use zip::{write::FileOptions, ZipWriter};
+
+/// Packs the export and the contents of the user's blobs into a zip archive.
+pub fn create_archive(
+    export: &UserExport,
+    blobs: Vec<(String, Vec<u8>)>,
+) -> Result<Vec<u8>, Error> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default();
+    zip.start_file("user.json", options)
+        .map_err(archive_error)?;
+    zip.write_all(&serde_json::to_vec_pretty(export).map_err(archive_error)?)
+        .map_err(archive_error)?;
+    for (path, content) in blobs {
+        zip.start_file(format!("blobs/{}", path), options)
+            .map_err(archive_error)?;
+        zip.write_all(&content).map_err(archive_error)?;
+    }
+    Ok(zip.finish().map_err(archive_error)?.into_inner())
+}
+
+fn archive_error(e: impl std::fmt::Display) -> Error {
+    Error::Status(Status::InternalServerError.code, e.to_string())
+}